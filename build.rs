@@ -22,7 +22,7 @@ impl Node {
     }
 
     fn add(&mut self, word: &[u8]) {
-        if word.len() == 0 {
+        if word.is_empty() {
             self.is_word = true;
         } else {
             let index = (word[0] - b'a') as usize;
@@ -135,7 +135,7 @@ fn main() {
     let wordlist = File::open("words.txt").expect("Couldn't open wordlist");
     BufReader::new(wordlist)
         .lines()
-        .flatten()
+        .map_while(Result::ok)
         .for_each(|word| root.add(word.as_bytes()));
 
     // Write output file
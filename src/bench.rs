@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The spread of a set of measurements
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Spread {
+    pub median: f64,
+    pub p95: f64,
+    pub max: f64,
+}
+
+impl Spread {
+    /// Summarise the given values, which needn't be sorted
+    pub fn of(values: &[f64]) -> Spread {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Spread {
+            median: percentile(&sorted, 50.0),
+            p95: percentile(&sorted, 95.0),
+            max: sorted.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+/// The nearest rank percentile of some sorted values, or 0 if there are none
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Timings and node counts from repeated runs of one kind of search
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub runs: usize,
+    pub time_ms: Spread,
+    pub nodes: Spread,
+}
+
+impl Summary {
+    /// Summarise runs, given as (milliseconds, nodes) pairs
+    pub fn of(runs: &[(f64, u64)]) -> Summary {
+        let times: Vec<f64> = runs.iter().map(|r| r.0).collect();
+        let nodes: Vec<f64> = runs.iter().map(|r| r.1 as f64).collect();
+        Summary {
+            runs: runs.len(),
+            time_ms: Spread::of(&times),
+            nodes: Spread::of(&nodes),
+        }
+    }
+}
+
+/// Results for a single puzzle file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PuzzleBench {
+    /// File name, used to match puzzles against a baseline
+    pub name: String,
+    pub solved: bool,
+    pub solve: Summary,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enumerate: Option<Summary>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solutions: Option<usize>,
+}
+
+/// The results of a whole benchmark run, which can be saved as a baseline
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub warmup: usize,
+    pub repeat: usize,
+    pub puzzles: Vec<PuzzleBench>,
+}
+
+impl BenchReport {
+    /// Summarise the median of each puzzle's solves across the corpus
+    pub fn aggregate(&self) -> Summary {
+        self.aggregate_of(|p| Some(&p.solve))
+    }
+
+    /// Like `aggregate`, for puzzles that were also enumerated
+    pub fn aggregate_enumerate(&self) -> Option<Summary> {
+        let summary = self.aggregate_of(|p| p.enumerate.as_ref());
+        (summary.runs > 0).then_some(summary)
+    }
+
+    fn aggregate_of(&self, pick: impl Fn(&PuzzleBench) -> Option<&Summary>) -> Summary {
+        let runs: Vec<(f64, u64)> = self
+            .puzzles
+            .iter()
+            .filter_map(pick)
+            .map(|s| (s.time_ms.median, s.nodes.median as u64))
+            .collect();
+        Summary::of(&runs)
+    }
+}
+
+/// A puzzle which got worse compared to a baseline
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Regression {
+    pub name: String,
+    /// What got worse, such as `solve time`
+    pub measure: String,
+    pub baseline: f64,
+    pub current: f64,
+}
+
+impl Regression {
+    /// How much worse, as a percentage of the baseline
+    pub fn percent(&self) -> f64 {
+        if self.baseline == 0.0 {
+            f64::INFINITY
+        } else {
+            (self.current / self.baseline - 1.0) * 100.0
+        }
+    }
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} went from {:.2} to {:.2} (+{:.0}%)",
+            self.name,
+            self.measure,
+            self.baseline,
+            self.current,
+            self.percent()
+        )
+    }
+}
+
+/// Find puzzles whose median time grew by more than `threshold` percent, or
+/// whose node count grew at all
+///
+/// Node counts don't depend on the machine, so any change means the search
+/// itself changed.
+pub fn compare(baseline: &BenchReport, current: &BenchReport, threshold: f64) -> Vec<Regression> {
+    let mut regressions = Vec::new();
+    for now in &current.puzzles {
+        let Some(before) = baseline.puzzles.iter().find(|p| p.name == now.name) else {
+            continue;
+        };
+        let pairs = [
+            ("solve", Some(&before.solve), Some(&now.solve)),
+            (
+                "enumerate",
+                before.enumerate.as_ref(),
+                now.enumerate.as_ref(),
+            ),
+        ];
+        for (kind, before, now_summary) in pairs {
+            let (Some(before), Some(after)) = (before, now_summary) else {
+                continue;
+            };
+            if after.time_ms.median > before.time_ms.median * (1.0 + threshold / 100.0) {
+                regressions.push(Regression {
+                    name: now.name.clone(),
+                    measure: format!("{} time (ms)", kind),
+                    baseline: before.time_ms.median,
+                    current: after.time_ms.median,
+                });
+            }
+            if after.nodes.median > before.nodes.median {
+                regressions.push(Regression {
+                    name: now.name.clone(),
+                    measure: format!("{} nodes", kind),
+                    baseline: before.nodes.median,
+                    current: after.nodes.median,
+                });
+            }
+        }
+    }
+    regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spread_of_runs() {
+        let values: Vec<f64> = (1..=20).map(f64::from).rev().collect();
+        let spread = Spread::of(&values);
+        assert_eq!(spread.median, 10.0);
+        assert_eq!(spread.p95, 19.0);
+        assert_eq!(spread.max, 20.0);
+        assert_eq!(Spread::of(&[3.0]).p95, 3.0);
+        assert_eq!(Spread::of(&[]), Spread::default());
+    }
+
+    fn puzzle(name: &str, ms: f64, nodes: u64) -> PuzzleBench {
+        PuzzleBench {
+            name: name.to_string(),
+            solved: true,
+            solve: Summary::of(&[(ms, nodes)]),
+            enumerate: None,
+            solutions: None,
+        }
+    }
+
+    #[test]
+    fn flags_slowdowns_against_baseline() {
+        let baseline = BenchReport {
+            puzzles: vec![puzzle("a", 10.0, 100), puzzle("b", 10.0, 100)],
+            ..BenchReport::default()
+        };
+        let current = BenchReport {
+            puzzles: vec![
+                puzzle("a", 10.5, 100),
+                puzzle("b", 12.0, 120),
+                puzzle("new", 99.0, 999),
+            ],
+            ..BenchReport::default()
+        };
+        let regressions = compare(&baseline, &current, 10.0);
+        let measures: Vec<(&str, &str)> = regressions
+            .iter()
+            .map(|r| (r.name.as_str(), r.measure.as_str()))
+            .collect();
+        assert_eq!(measures, [("b", "solve time (ms)"), ("b", "solve nodes")]);
+        assert!((regressions[0].percent() - 20.0).abs() < 1e-9);
+        assert_eq!(current.aggregate().time_ms.max, 99.0);
+    }
+}
@@ -1,10 +1,11 @@
 use std::fmt;
 
 /// A bitarray of length 8
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
 pub struct Bitset (u8);
 
 impl Bitset {
+    #[allow(dead_code)]
     pub fn new() -> Bitset {
         Bitset(0)
     }
@@ -40,11 +41,13 @@ impl Bitset {
     }
 
     /// Test if any bits are set to true
+    #[allow(dead_code)]
     pub fn any(self) -> bool {
         self.0 != 0
     }
 
     /// Tests if all bits are false
+    #[allow(dead_code)]
     pub fn is_empty(self) -> bool {
         self.0 == 0
     }
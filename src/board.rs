@@ -1,12 +1,62 @@
-use crate::{dictionary, Point, PointSet, Puzzle};
+use crate::constraint::Constraints;
+use crate::dictionary::{self, Dictionary};
+use crate::frame::{Frame, Reason, Rejected};
+use crate::{Point, PointSet, Puzzle};
 use colored::{ColoredString, Colorize};
+use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::ops::Index;
+use std::ops::{ControlFlow, Index};
+use std::time::Instant;
 
 #[derive(Clone)]
 pub struct Board {
     letters: [[u8; 7]; 12],
     word_ids: [[i8; 7]; 12],
+    dict: Dictionary,
+    constraints: Constraints,
+    stats: SearchStats,
+    /// Shortest and longest words allowed, from the puzzle
+    min_size: usize,
+    max_size: usize,
+}
+
+/// Counters describing how much work a search did
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchStats {
+    /// Number of words placed while searching
+    pub nodes: u64,
+    /// Number of placed words which were later taken back
+    pub backtracks: u64,
+}
+
+/// A word which has been placed on the board
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlacedWord {
+    /// Position of the word in reading order, starting at 0
+    pub id: usize,
+    pub word: String,
+    /// Cells covered by the word, in reading order
+    pub points: PointSet,
+}
+
+/// Returned when a search runs past its deadline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut;
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "search timed out")
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Fail with `TimedOut` if the deadline has passed
+fn check_deadline(deadline: Option<Instant>) -> Result<(), TimedOut> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => Err(TimedOut),
+        _ => Ok(()),
+    }
 }
 
 impl Board {
@@ -14,6 +64,114 @@ impl Board {
         Board {
             letters: [[0; 7]; 12],
             word_ids: [[-1; 7]; 12],
+            dict: Dictionary::builtin(),
+            constraints: Constraints::default(),
+            stats: SearchStats::default(),
+            min_size: 4,
+            max_size: 8,
+        }
+    }
+
+    /// Work done by searches on this board since the last reset
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = SearchStats::default();
+    }
+
+    /// Count work done by a search which doesn't go through the board itself
+    pub(crate) fn add_stats(&mut self, stats: SearchStats) {
+        self.stats.nodes += stats.nodes;
+        self.stats.backtracks += stats.backtracks;
+    }
+
+    /// Return the letters of each row
+    pub fn rows(&self) -> Vec<String> {
+        self.letters
+            .iter()
+            .map(|row| row.iter().map(|&c| c as char).collect())
+            .collect()
+    }
+
+    /// Return the words placed so far, in the order they were placed
+    pub fn words(&self) -> Vec<PlacedWord> {
+        let mut words: Vec<PlacedWord> = Vec::with_capacity(self.n_words());
+        for y in 0..12 {
+            for x in 0..7 {
+                let id = self.word_ids[y][x];
+                if id == -1 {
+                    continue;
+                }
+                let id = id as usize;
+                while words.len() <= id {
+                    words.push(PlacedWord {
+                        id: words.len(),
+                        word: String::new(),
+                        points: PointSet::empty(),
+                    });
+                }
+                words[id].word.push(self.letters[y][x] as char);
+                words[id].points.push(Point::new(x as u8, y as u8));
+            }
+        }
+        words
+    }
+
+    /// Use a different dictionary for finding words
+    pub fn set_dictionary(&mut self, dict: Dictionary) {
+        self.dict = dict;
+    }
+
+    pub fn dictionary(&self) -> Dictionary {
+        self.dict
+    }
+
+    /// The fewest and most letters a word may have
+    pub fn word_sizes(&self) -> (usize, usize) {
+        (self.min_size, self.max_size)
+    }
+
+    /// Limit the length of words, which must be between 1 and 8 letters
+    pub fn set_word_sizes(&mut self, min: usize, max: usize) -> Result<(), PuzzleError> {
+        if min == 0 || min > max || max > 8 {
+            return Err(PuzzleError::WordSizes { min, max });
+        }
+        self.min_size = min;
+        self.max_size = max;
+        Ok(())
+    }
+
+    /// Search under extra rules, on top of the puzzle's own
+    pub fn set_constraints(&mut self, constraints: Constraints) {
+        self.constraints = constraints;
+    }
+
+    pub fn constraints(&self) -> &Constraints {
+        &self.constraints
+    }
+
+    /// Test if a full board meets every constraint
+    pub fn is_solution(&self) -> bool {
+        self.is_done() && self.constraints.allow_solution(self)
+    }
+
+    /// Test if both boards have the same letters, split into the same words
+    pub fn same_solution(&self, other: &Board) -> bool {
+        self.letters == other.letters && self.word_ids == other.word_ids
+    }
+
+    /// Clear every placed word, leaving only the letters
+    pub fn reset(&mut self) {
+        self.word_ids = [[-1; 7]; 12];
+    }
+
+    /// Return the id of the word covering the given point, if any
+    pub fn word_id(&self, point: Point) -> Option<usize> {
+        match self.word_ids[point.y as usize][point.x as usize] {
+            -1 => None,
+            id => Some(id as usize),
         }
     }
 
@@ -41,8 +199,10 @@ impl Board {
         (max + 1) as usize
     }
 
-    /// Add the given point set to the current board
-    fn insert_word(&mut self, points: &PointSet) {
+    /// Add the given point set to the current board, as the next word
+    ///
+    /// No checks are made, see `next_words` for the legal choices.
+    pub fn insert_word(&mut self, points: &PointSet) {
         let word_id = self.n_words() as i8;
         points
             .clone()
@@ -52,7 +212,7 @@ impl Board {
 
     /// Remove the given point set
     /// It is up to the user to only call this on the most recent set of points
-    fn remove_word(&mut self, points: &PointSet) {
+    pub fn remove_word(&mut self, points: &PointSet) {
         points
             .clone()
             .into_iter()
@@ -60,7 +220,7 @@ impl Board {
     }
 
     /// Return the uppermost, leftmost, currently unmarked point
-    fn get_root(&self) -> Option<Point> {
+    pub fn get_root(&self) -> Option<Point> {
         for y in 0..12 {
             for x in 0..7 {
                 if self.word_ids[y][x] == -1 {
@@ -68,55 +228,246 @@ impl Board {
                 }
             }
         }
-        return None;
+        None
     }
 
     /// Return a list of all valid solutions to the board
     pub fn enumerate_solutions(&mut self) -> Vec<Board> {
         let mut solutions = Vec::new();
+        let _ = self.enumerate_solutions_until(None, &mut |board| {
+            solutions.push(board.clone());
+            ControlFlow::Continue(())
+        });
+        solutions
+    }
 
-        self.enumerate_solutions_inner(&mut solutions);
+    /// Call `found` with each solution as it is found
+    ///
+    /// The search stops early when `found` returns `Break`, and gives up once
+    /// the deadline has passed.
+    pub fn enumerate_solutions_until(
+        &mut self,
+        deadline: Option<Instant>,
+        found: &mut dyn FnMut(&Board) -> ControlFlow<()>,
+    ) -> Result<(), TimedOut> {
+        if self.is_done() {
+            // Words placed before the search may already cover the board
+            if self.is_solution() {
+                let _ = found(self);
+            }
+            return Ok(());
+        }
+        self.enumerate_solutions_inner(deadline, None, found)
+            .map(|_| ())
+    }
 
-        return solutions;
+    /// Call `found` with each solution like `enumerate_solutions_until`, but
+    /// give up rather than place more than `max_nodes` words
+    ///
+    /// Returns false if the search was cut off by running out of nodes, and
+    /// true if it finished or `found` stopped it.
+    pub fn enumerate_solutions_within(
+        &mut self,
+        deadline: Option<Instant>,
+        max_nodes: u64,
+        found: &mut dyn FnMut(&Board) -> ControlFlow<()>,
+    ) -> Result<bool, TimedOut> {
+        let mut stopped = false;
+        let mut found = |board: &Board| {
+            let res = found(board);
+            stopped = res.is_break();
+            res
+        };
+        if self.is_done() {
+            if self.is_solution() {
+                let _ = found(self);
+            }
+            return Ok(true);
+        }
+        let limit = self.stats.nodes.saturating_add(max_nodes);
+        let res = self.enumerate_solutions_inner(deadline, Some(limit), &mut found)?;
+        Ok(res.is_continue() || stopped)
     }
 
-    fn enumerate_solutions_inner(&mut self, solutions: &mut Vec<Board>) {
+    fn enumerate_solutions_inner(
+        &mut self,
+        deadline: Option<Instant>,
+        max_nodes: Option<u64>,
+        found: &mut dyn FnMut(&Board) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, TimedOut> {
+        check_deadline(deadline)?;
         for word in self.next_words().iter() {
+            if max_nodes.is_some_and(|max| self.stats.nodes >= max) {
+                return Ok(ControlFlow::Break(()));
+            }
             self.insert_word(word);
-            if self.is_done() {
-                solutions.push(self.clone());
+            self.stats.nodes += 1;
+            let res = if self.is_solution() {
+                Ok(found(self))
+            } else if self.is_done() {
+                Ok(ControlFlow::Continue(()))
             } else {
-                self.enumerate_solutions_inner(solutions)
-            }
+                self.enumerate_solutions_inner(deadline, max_nodes, found)
+            };
             self.remove_word(word);
+            self.stats.backtracks += 1;
+            if res != Ok(ControlFlow::Continue(())) {
+                return res;
+            }
         }
+        Ok(ControlFlow::Continue(()))
     }
 
     /// Solve the board
     pub fn solve(&mut self) -> bool {
         //println!("Solving:");
         //println!("{}", self);
-        for word in self.next_words().iter() {
+        self.solve_until(None).unwrap()
+    }
+
+    /// Solve the board, giving up once the deadline has passed
+    ///
+    /// On a timeout the board is left as it was before the call.
+    pub fn solve_until(&mut self, deadline: Option<Instant>) -> Result<bool, TimedOut> {
+        self.solve_inner(deadline, None)
+    }
+
+    /// Solve the board like `solve_until`, passing each step of the search to
+    /// `trace` as it happens
+    pub fn solve_traced(
+        &mut self,
+        deadline: Option<Instant>,
+        trace: &mut dyn FnMut(Frame),
+    ) -> Result<bool, TimedOut> {
+        self.solve_inner(deadline, Some(trace))
+    }
+
+    fn solve_inner<'t>(
+        &mut self,
+        deadline: Option<Instant>,
+        mut trace: Option<&mut (dyn FnMut(Frame) + 't)>,
+    ) -> Result<bool, TimedOut> {
+        check_deadline(deadline)?;
+        if self.is_done() {
+            let solved = self.is_solution();
+            if let (Some(trace), true) = (trace, solved) {
+                trace(Frame::Solved);
+            }
+            return Ok(solved);
+        }
+        let depth = self.n_words();
+        let words = match trace.as_deref_mut() {
+            Some(trace) => {
+                let mut rejected = Vec::new();
+                let words = self.candidates(Some(&mut rejected));
+                trace(Frame::Candidates {
+                    depth,
+                    root: self.get_root().unwrap(),
+                    words: words.clone(),
+                    rejected,
+                });
+                words
+            }
+            None => self.next_words(),
+        };
+        for word in words.iter() {
             self.insert_word(word);
-            if self.is_done() {
-                return true;
-            } else if self.solve() {
-                return true;
+            self.stats.nodes += 1;
+            if let Some(trace) = trace.as_deref_mut() {
+                trace(Frame::Insert {
+                    depth,
+                    word: self.spell(word),
+                    points: word.clone(),
+                });
+            }
+            let res = if self.is_solution() {
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace(Frame::Solved);
+                }
+                Ok(true)
+            } else if self.is_done() {
+                Ok(false)
+            } else {
+                self.solve_inner(deadline, trace.as_deref_mut())
+            };
+            if res != Ok(false) {
+                if res.is_err() {
+                    self.remove_word(word);
+                }
+                return res;
             }
             self.remove_word(word);
+            self.stats.backtracks += 1;
+            if let Some(trace) = trace.as_deref_mut() {
+                trace(Frame::Remove {
+                    depth,
+                    points: word.clone(),
+                });
+            }
         }
-        return false;
+        Ok(false)
     }
 
-    /// For a given state, return a list of valid next words
+    /// Return the letters under the given points, in order
+    pub fn spell(&self, points: &PointSet) -> String {
+        points.points().iter().map(|&p| self[p] as char).collect()
+    }
+
+    /// For a given state, return a list of valid next words, which is empty
+    /// once the board is full
     /// TODO, return a &'static [u8] from the tree
     pub fn next_words(&self) -> Vec<PointSet> {
-        let root_point = self.get_root().unwrap();
+        self.candidates(None)
+    }
+
+    /// Find the valid next words, optionally recording every partial word
+    /// that was given up on and why
+    fn candidates(&self, mut rejected: Option<&mut Vec<Rejected>>) -> Vec<PointSet> {
+        let Some(root_point) = self.get_root() else {
+            return Vec::new();
+        };
         let points = PointSet::new(root_point);
         let mut words = Vec::new();
-        let dict_node = dictionary::first_node(self[root_point]).unwrap();
-        self.list_words(points, dict_node, &mut words);
-        return words;
+        match self.dict.first_node(self[root_point]) {
+            Some(dict_node) => self.list_words_inner(points, dict_node, &mut words, &mut rejected),
+            None => {
+                if let Some(rejected) = rejected {
+                    rejected.push(Rejected {
+                        points,
+                        reason: Reason::NoSuchWord,
+                    });
+                }
+            }
+        }
+        words
+    }
+
+    /// Return every word which could start at the given free cell
+    ///
+    /// Unlike `next_words` the cell needn't be the root, and no check is made
+    /// that the free cells before it can still be covered.
+    pub fn words_from(&self, start: Point) -> Vec<PointSet> {
+        let mut words = Vec::new();
+        if self.word_id(start).is_none() {
+            if let Some(dict_node) = self.dict.first_node(self[start]) {
+                self.list_words(PointSet::new(start), dict_node, &mut words);
+            }
+        }
+        words
+    }
+
+    /// Return every word which could be traced on the free cells, from any
+    /// start, whether or not the rest of the board could then be tiled
+    ///
+    /// Words are grouped by their first cell, in reading order.
+    pub fn all_words(&self) -> Vec<PointSet> {
+        let mut words = Vec::new();
+        for y in 0..12 {
+            for x in 0..7 {
+                words.extend(self.words_from(Point::new(x, y)));
+            }
+        }
+        words
     }
 
     /// Recursively enumerate all possible words
@@ -125,64 +476,115 @@ impl Board {
         points: PointSet,
         dict_node: &'static dictionary::Node,
         list: &mut Vec<PointSet>,
+    ) {
+        self.list_words_inner(points, dict_node, list, &mut None)
+    }
+
+    fn list_words_inner(
+        &self,
+        points: PointSet,
+        dict_node: &'static dictionary::Node,
+        list: &mut Vec<PointSet>,
+        rejected: &mut Option<&mut Vec<Rejected>>,
     ) {
         if !points.connectable() {
             // If the given points aren't connectable, return early
+            if let Some(rejected) = rejected {
+                rejected.push(Rejected {
+                    points,
+                    reason: Reason::NotConnectable,
+                });
+            }
             return;
         }
         // First check if we're currently a word
-        if dict_node.is_word && points.contiguous() {
-            list.push(points.clone());
+        if dict_node.is_word && points.length() >= self.min_size {
+            if !points.contiguous() {
+                if let Some(rejected) = rejected {
+                    rejected.push(Rejected {
+                        points: points.clone(),
+                        reason: Reason::NotContiguous,
+                    });
+                }
+            } else if self.constraints.allow_word(self, &points) {
+                list.push(points.clone());
+            } else if let Some(rejected) = rejected {
+                rejected.push(Rejected {
+                    points: points.clone(),
+                    reason: Reason::Constrained,
+                });
+            }
         }
 
-        if points.length() < 8 {
+        if points.length() < self.max_size {
             // Try to add 1 character to the word
             let last_point = points.last_point();
             for x in last_point.x + 1..7 {
-                if self.visited(x as usize, last_point.y as usize) {
-                    continue;
-                }
                 // Consider point (x, last_point.y)
-                let letter = self.letters[last_point.y as usize][x as usize];
-                if let Some(next_node) = dict_node.get_next(letter) {
-                    let mut next_points = points.clone();
-                    next_points.push(Point::new(x, last_point.y));
-                    self.list_words(next_points, next_node, list);
-                }
+                self.extend_word(
+                    &points,
+                    dict_node,
+                    Point::new(x, last_point.y),
+                    list,
+                    rejected,
+                );
             }
             if last_point.y < 11 {
                 let y = last_point.y + 1;
                 for x in 0..=last_point.x {
-                    if self.visited(x as usize, y as usize) {
-                        continue;
-                    }
-                    let letter = self.letters[y as usize][x as usize];
-                    if let Some(next_node) = dict_node.get_next(letter) {
-                        let mut next_points = points.clone();
-                        next_points.push(Point::new(x, y));
-                        self.list_words(next_points, next_node, list);
-                    }
+                    self.extend_word(&points, dict_node, Point::new(x, y), list, rejected);
+                }
+            }
+        }
+    }
+
+    /// Continue the search for words with one more point added
+    fn extend_word(
+        &self,
+        points: &PointSet,
+        dict_node: &'static dictionary::Node,
+        point: Point,
+        list: &mut Vec<PointSet>,
+        rejected: &mut Option<&mut Vec<Rejected>>,
+    ) {
+        if self.visited(point.x as usize, point.y as usize) {
+            return;
+        }
+        match dict_node.get_next(self[point]) {
+            Some(next_node) => {
+                let mut next_points = points.clone();
+                next_points.push(point);
+                self.list_words_inner(next_points, next_node, list, rejected);
+            }
+            None => {
+                if let Some(rejected) = rejected {
+                    let mut next_points = points.clone();
+                    next_points.push(point);
+                    rejected.push(Rejected {
+                        points: next_points,
+                        reason: Reason::NoSuchWord,
+                    });
                 }
             }
         }
     }
 
     /// Test if a horizontal connection exists between (x, y) and (x+1, y)
-    fn is_h_connection(&self, x: usize, y: usize) -> bool {
+    pub(crate) fn is_h_connection(&self, x: usize, y: usize) -> bool {
         debug_assert!(x <= 5);
         debug_assert!(y <= 11);
         self.word_ids[y][x] != -1 && (self.word_ids[y][x] == self.word_ids[y][x + 1])
     }
 
     /// Test if a vertical connection exists between (x, y) and (x, y+1)
-    fn is_v_connection(&self, x: usize, y: usize) -> bool {
+    pub(crate) fn is_v_connection(&self, x: usize, y: usize) -> bool {
         debug_assert!(x <= 6);
         debug_assert!(y <= 10);
         self.word_ids[y][x] != -1 && (self.word_ids[y][x] == self.word_ids[y + 1][x])
     }
 
     /// Test if there's a diagonal connection
-    fn is_d_connection(&self, x: usize, y: usize) -> bool {
+    pub(crate) fn is_d_connection(&self, x: usize, y: usize) -> bool {
         let id = self.word_ids[y][x];
 
         id != -1
@@ -245,7 +647,12 @@ impl Board {
     /// Print the row of connections
     fn fmt_connect_row(&self, f: &mut fmt::Formatter<'_>, index: usize) -> fmt::Result {
         for x in 0..6 {
-            write!(f, "{}{}", self.disp_v_conn(x, index), self.disp_d_conn(x, index))?;
+            write!(
+                f,
+                "{}{}",
+                self.disp_v_conn(x, index),
+                self.disp_d_conn(x, index)
+            )?;
         }
         writeln!(f, "{}", self.disp_v_conn(6, index))
     }
@@ -269,64 +676,422 @@ impl fmt::Display for Board {
     }
 }
 
-impl From<Puzzle> for Board {
-    fn from(value: Puzzle) -> Board {
+/// Build an unsolved board from a puzzle, checking the puzzle is well formed
+impl TryFrom<Puzzle> for Board {
+    type Error = PuzzleError;
+
+    fn try_from(value: Puzzle) -> Result<Board, PuzzleError> {
+        if value.width != 7 || value.height != 12 {
+            return Err(PuzzleError::Size {
+                width: value.width,
+                height: value.height,
+            });
+        }
         let mut res = Board::new();
-        assert_eq!(value.width, 7);
-        assert_eq!(value.height, 12);
-        assert_eq!(value.minSize, 4);
-        assert_eq!(value.maxSize, 8);
-        assert_eq!(value.regions.len(), value.words.len());
-        value
-            .regions
-            .iter()
-            .zip(value.words.iter())
-            .for_each(|(region, word)| {
-                region
-                    .iter()
-                    .zip(word.as_bytes().iter())
-                    .for_each(|(point, c)| res.letters[point[1]][point[0]] = *c)
+        res.set_word_sizes(value.min_size, value.max_size)?;
+        if value.regions.len() != value.words.len() {
+            return Err(PuzzleError::Counts {
+                regions: value.regions.len(),
+                words: value.words.len(),
             });
-        return res;
+        }
+        let mut covered = [[false; 7]; 12];
+        for (region, (cells, word)) in value.regions.iter().zip(&value.words).enumerate() {
+            if cells.len() != word.len() {
+                return Err(PuzzleError::Length {
+                    region,
+                    cells: cells.len(),
+                    letters: word.len(),
+                });
+            }
+            if !word.bytes().all(|c| c.is_ascii_lowercase()) {
+                return Err(PuzzleError::NotLetters {
+                    region,
+                    word: word.clone(),
+                });
+            }
+            for (&[x, y], &c) in cells.iter().zip(word.as_bytes()) {
+                if x >= 7 || y >= 12 {
+                    return Err(PuzzleError::OutOfRange { region, x, y });
+                }
+                if covered[y][x] {
+                    return Err(PuzzleError::Overlap { x, y });
+                }
+                covered[y][x] = true;
+                res.letters[y][x] = c;
+            }
+        }
+        for (y, row) in covered.iter().enumerate() {
+            if let Some(x) = row.iter().position(|&c| !c) {
+                return Err(PuzzleError::Uncovered { x, y });
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl Board {
+    /// Build the board with the puzzle's own solution filled in
+    pub fn official_solution(puzzle: &Puzzle) -> Result<Board, PuzzleError> {
+        let mut board = Board::try_from(puzzle.clone())?;
+        for region in puzzle.official_regions() {
+            board.insert_word(&region);
+        }
+        Ok(board)
+    }
+
+    /// Test if the given words, in order, are a legal way to start the board
+    ///
+    /// Returns the index of the first word which can't be placed.
+    pub fn check_words(&self, words: &[PointSet]) -> Result<(), usize> {
+        let mut board = self.clone();
+        for (i, word) in words.iter().enumerate() {
+            if board.is_done() || !board.next_words().contains(&word.sorted()) {
+                return Err(i);
+            }
+            board.insert_word(word);
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug)]
+/// Problems found while building a board from a puzzle
+///
+/// Regions count from 0, in the order the puzzle lists them, and cells are
+/// given as `x, y` from the top left.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// The board isn't 7 wide and 12 high
+    Size { width: usize, height: usize },
+    /// The word length limits aren't between 1 and 8, smallest first
+    WordSizes { min: usize, max: usize },
+    /// There isn't one word per region
+    Counts { regions: usize, words: usize },
+    /// A region's word has a different number of letters to its cells
+    Length {
+        region: usize,
+        cells: usize,
+        letters: usize,
+    },
+    /// A region's word has something other than the letters a to z
+    NotLetters { region: usize, word: String },
+    /// A region has a cell off the board
+    OutOfRange { region: usize, x: usize, y: usize },
+    /// A cell is in more than one region
+    Overlap { x: usize, y: usize },
+    /// A cell isn't in any region
+    Uncovered { x: usize, y: usize },
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::Size { width, height } => write!(
+                f,
+                "puzzle is {} by {}, but a board is 7 by 12",
+                width, height
+            ),
+            PuzzleError::WordSizes { min, max } => {
+                write!(f, "word sizes {} to {} aren't between 1 and 8", min, max)
+            }
+            PuzzleError::Counts { regions, words } => {
+                write!(f, "puzzle has {} regions but {} words", regions, words)
+            }
+            PuzzleError::Length {
+                region,
+                cells,
+                letters,
+            } => write!(
+                f,
+                "region {} has {} cells but its word has {} letters",
+                region, cells, letters
+            ),
+            PuzzleError::NotLetters { region, word } => write!(
+                f,
+                "region {}: {:?} isn't made of the letters a-z",
+                region, word
+            ),
+            PuzzleError::OutOfRange { region, x, y } => {
+                write!(f, "region {}: cell ({}, {}) is off the board", region, x, y)
+            }
+            PuzzleError::Overlap { x, y } => {
+                write!(f, "cell ({}, {}) is in more than one region", x, y)
+            }
+            PuzzleError::Uncovered { x, y } => {
+                write!(f, "cell ({}, {}) isn't in any region", x, y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+/// Problems found while reading a board from a letter grid
+///
+/// Lines and columns count from 1, as in a text editor.
+#[derive(Debug, PartialEq, Eq)]
 pub enum ParseError {
-    UnexpectedChar(u8),
-    MissingChar { line: usize, character: usize },
+    /// Something other than a letter, space, or comment
+    UnexpectedChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    /// A row ended before it had 7 letters
+    MissingChar { line: usize, column: usize },
+    /// A row had more than 7 letters
+    ExtraChar { line: usize, column: usize },
+    /// The text ended before 12 rows were read
+    MissingRows { found: usize },
+    /// There was another row after the 12th
+    ExtraRow { line: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {}, column {}: expected a letter, found {:?}",
+                line, column, found
+            ),
+            ParseError::MissingChar { line, column } => write!(
+                f,
+                "line {}, column {}: row ends early, every row needs 7 letters",
+                line, column
+            ),
+            ParseError::ExtraChar { line, column } => write!(
+                f,
+                "line {}, column {}: row is too long, every row needs 7 letters",
+                line, column
+            ),
+            ParseError::MissingRows { found } => {
+                write!(f, "found {} rows, but a board needs 12", found)
+            }
+            ParseError::ExtraRow { line } => {
+                write!(f, "line {}: too many rows, a board needs 12", line)
+            }
+        }
+    }
 }
 
+impl std::error::Error for ParseError {}
+
+/// Parse a board from a grid of 12 rows of 7 letters
+///
+/// Letters may be either case, and may be separated by spaces. Blank lines,
+/// and lines starting with `#`, are skipped.
 impl TryFrom<&str> for Board {
     type Error = ParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let mut res = Board::new();
-        let mut bytes = value.as_bytes().iter();
-        for y in 0..12 {
-            for x in 0..7 {
-                match bytes.next() {
-                    Some(&c) if c >= b'a' && c <= b'z' => res.letters[y][x] = c,
-                    Some(&c) => return Err(ParseError::UnexpectedChar(c)),
-                    None => {
-                        return Err(ParseError::MissingChar {
-                            line: y,
-                            character: x,
-                        })
-                    }
-                };
-            }
-            match bytes.next() {
-                Some(b'\n') => {}
-                Some(&c) => return Err(ParseError::UnexpectedChar(c)),
-                None => {
-                    return Err(ParseError::MissingChar {
-                        line: y,
-                        character: 7,
-                    })
+        let mut y = 0;
+        for (i, line) in value.lines().enumerate() {
+            let line_no = i + 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            if y == 12 {
+                return Err(ParseError::ExtraRow { line: line_no });
+            }
+            let mut x = 0;
+            for (j, c) in line.chars().enumerate() {
+                let column = j + 1;
+                if c.is_whitespace() {
+                    continue;
                 }
+                if !c.is_ascii_alphabetic() {
+                    return Err(ParseError::UnexpectedChar {
+                        line: line_no,
+                        column,
+                        found: c,
+                    });
+                }
+                if x == 7 {
+                    return Err(ParseError::ExtraChar {
+                        line: line_no,
+                        column,
+                    });
+                }
+                res.letters[y][x] = c.to_ascii_lowercase() as u8;
+                x += 1;
+            }
+            if x < 7 {
+                return Err(ParseError::MissingChar {
+                    line: line_no,
+                    column: line.trim_end().chars().count() + 1,
+                });
             }
+            y += 1;
+        }
+        if y < 12 {
+            return Err(ParseError::MissingRows { found: y });
         }
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{row_puzzle, GRID};
+
+    #[test]
+    fn parse_grid() {
+        let board = Board::try_from(GRID).unwrap();
+        assert_eq!(board[Point::new(0, 0)], b'f');
+        assert_eq!(board[Point::new(6, 11)], b'y');
+    }
+
+    #[test]
+    fn parse_relaxed_grid() {
+        let text = format!(
+            "# Copied from a printout\r\n\r\n{}",
+            GRID.to_uppercase()
+                .replace("FLATCAR", "F L A T C A R")
+                .replace('\n', "\r\n")
+        );
+        let board = Board::try_from(text.as_str()).unwrap();
+        assert!(board.letters == Board::try_from(GRID).unwrap().letters);
+    }
+
+    #[test]
+    fn parse_errors_have_positions() {
+        let text = GRID.replacen("snippet", "snip3et", 1);
+        let err = Board::try_from(text.as_str()).err().unwrap();
+        assert_eq!(
+            err,
+            ParseError::UnexpectedChar {
+                line: 2,
+                column: 5,
+                found: '3'
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 5: expected a letter, found '3'"
+        );
+
+        let text = GRID.replacen("snippet", "snip", 1);
+        assert_eq!(
+            Board::try_from(text.as_str()).err(),
+            Some(ParseError::MissingChar { line: 2, column: 5 })
+        );
+        assert_eq!(
+            Board::try_from("flatcar\n").err(),
+            Some(ParseError::MissingRows { found: 1 })
+        );
+    }
+
+    #[test]
+    fn solve_parsed_grid() {
+        let mut board = Board::try_from(GRID).unwrap();
+        assert!(board.solve());
+        assert!(board.is_done());
+    }
+
+    #[test]
+    fn puzzles_are_checked() {
+        let board = Board::try_from(row_puzzle()).unwrap();
+        assert!(board.letters == Board::try_from(GRID).unwrap().letters);
+
+        let check = |change: &dyn Fn(&mut Puzzle)| {
+            let mut puzzle = row_puzzle();
+            change(&mut puzzle);
+            Board::try_from(puzzle).err().unwrap()
+        };
+        assert_eq!(
+            check(&|p| p.width = 8),
+            PuzzleError::Size {
+                width: 8,
+                height: 12
+            }
+        );
+        assert_eq!(
+            check(&|p| p.regions[0][0] = [9, 0]),
+            PuzzleError::OutOfRange {
+                region: 0,
+                x: 9,
+                y: 0
+            }
+        );
+        assert_eq!(
+            check(&|p| p.regions[0][0] = [0, 1]),
+            PuzzleError::Overlap { x: 0, y: 1 }
+        );
+        assert_eq!(
+            check(&|p| {
+                p.regions[0].clear();
+                p.words[0].clear();
+            }),
+            PuzzleError::Uncovered { x: 0, y: 0 }
+        );
+        assert_eq!(
+            check(&|p| p.words[0] = "FLATCAR".to_string()),
+            PuzzleError::NotLetters {
+                region: 0,
+                word: "FLATCAR".to_string()
+            }
+        );
+        assert_eq!(
+            check(&|p| p.words[0].pop().map(|_| ()).unwrap()),
+            PuzzleError::Length {
+                region: 0,
+                cells: 7,
+                letters: 6
+            }
+        );
+        assert_eq!(
+            check(&|p| p.min_size = 0),
+            PuzzleError::WordSizes { min: 0, max: 8 }
+        );
+    }
+
+    #[test]
+    fn word_sizes_come_from_the_puzzle() {
+        let sizes = |min, max| {
+            let board = Board::try_from(Puzzle {
+                min_size: min,
+                max_size: max,
+                ..row_puzzle()
+            })
+            .unwrap();
+            let words = board.all_words();
+            let lengths = words.iter().map(|w| w.length());
+            (lengths.clone().min().unwrap(), lengths.max().unwrap())
+        };
+        assert_eq!(sizes(4, 8), (4, 8));
+        assert_eq!(sizes(5, 6), (5, 6));
+
+        // Only the rows are left when every word is 7 letters
+        let mut board = Board::try_from(Puzzle {
+            min_size: 7,
+            max_size: 7,
+            ..row_puzzle()
+        })
+        .unwrap();
+        assert!(board.solve());
+        let words: Vec<String> = board.words().into_iter().map(|w| w.word).collect();
+        assert_eq!(words, GRID.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn placed_words() {
+        let mut board = Board::try_from(GRID).unwrap();
+        assert!(board.solve());
+        let words = board.words();
+        assert_eq!(words.len(), board.n_words());
+        assert_eq!(words.iter().map(|w| w.points.length()).sum::<usize>(), 84);
+        assert_eq!(words[0].id, 0);
+        assert!(board.dictionary().is_word(words[0].word.as_bytes()));
+
+        let json = serde_json::to_value(&words[0]).unwrap();
+        assert_eq!(json["points"][0], serde_json::json!([0, 0]));
+    }
+}
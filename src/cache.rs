@@ -0,0 +1,70 @@
+use crate::Puzzle;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// An on disk store of downloaded puzzles, one json file per id
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// Open the cache stored in the given directory
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Cache {
+        Cache { dir: dir.into() }
+    }
+
+    /// Open the cache in the default location
+    pub fn open_default() -> Cache {
+        Cache::new(Cache::default_dir())
+    }
+
+    /// `$CELL_TOWER_CACHE`, falling back to `$XDG_CACHE_HOME/cell-tower`
+    /// and then `~/.cache/cell-tower`
+    pub fn default_dir() -> PathBuf {
+        if let Some(dir) = env::var_os("CELL_TOWER_CACHE") {
+            return dir.into();
+        }
+        let base = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .unwrap_or_else(|| PathBuf::from("."));
+        base.join("cell-tower")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Location of the given puzzle within the cache
+    pub fn path(&self, id: usize) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Test if the given puzzle has already been stored
+    pub fn contains(&self, id: usize) -> bool {
+        self.path(id).is_file()
+    }
+
+    /// Load a puzzle, returning `None` if it hasn't been stored
+    pub fn get(&self, id: usize) -> io::Result<Option<Puzzle>> {
+        match File::open(self.path(id)) {
+            Ok(file) => Ok(Some(serde_json::from_reader(BufReader::new(file))?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Store a puzzle, replacing any previous copy
+    pub fn put(&self, id: usize, puzzle: &Puzzle) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        // Write to a temporary file first, so a crash never leaves half a puzzle
+        let tmp = self.dir.join(format!(".{}.json.tmp", id));
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, puzzle)?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(tmp, self.path(id))
+    }
+}
@@ -0,0 +1,1942 @@
+use crate::bench::{self, BenchReport, PuzzleBench, Summary};
+use crate::board::{PuzzleError, TimedOut};
+use crate::cache::Cache;
+use crate::client::{self, Client};
+use crate::cnf::{Cnf, CnfError};
+use crate::constraint::{Banned, Constraint, Constraints, Edge, Required, Shape};
+use crate::coverage::{self, Coverage};
+use crate::deduce;
+use crate::dictionary::{self, Dictionary};
+use crate::diff::{self, Comparison};
+use crate::difficulty::{self, Band, Difficulty};
+use crate::dlx::{self, Engine};
+use crate::frequency::{Frequencies, Tier};
+use crate::generate::{self, GenerateError, Recipe};
+use crate::given::{self, Given, GivenFile};
+use crate::hint::{Hints, Level};
+use crate::html;
+use crate::play::{self, Game};
+use crate::render::{self, Style};
+use crate::report::{millis, PuzzleInfo, SolutionLine, SolveReport};
+use crate::svg::{self, SvgOptions};
+use crate::trace::{self, Trace, TraceWriter};
+use crate::traceable;
+use crate::{Board, Puzzle};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::json;
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Exit codes, so the tool can be driven from scripts
+pub mod exit {
+    /// Everything worked
+    pub const OK: u8 = 0;
+    /// The command ran, but the answer was no: no solution, a failed check,
+    /// or a missing word
+    pub const FAILED: u8 = 1;
+    /// The command line couldn't be understood
+    pub const USAGE: u8 = 2;
+    /// Something went wrong loading a puzzle or dictionary
+    pub const ERROR: u8 = 3;
+    /// The search didn't finish within `--timeout`
+    pub const TIMEOUT: u8 = 4;
+}
+
+/// A solver for the word game cell-tower
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Args)]
+pub struct GlobalArgs {
+    /// How results are printed
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+    /// When to use colour in the output
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+    /// How boards are drawn, defaults to colour on a terminal and box
+    /// drawing lines otherwise
+    #[arg(long, global = true, value_enum)]
+    pub render: Option<Style>,
+    /// Word list to use instead of the built in one, one word per line
+    #[arg(long, global = true, value_name = "FILE")]
+    pub dict: Option<PathBuf>,
+    /// Give up searching after this many seconds
+    #[arg(long, global = true, value_name = "SECONDS")]
+    pub timeout: Option<f64>,
+    /// Words ordered from most to least common, one per line, used to judge
+    /// difficulty
+    #[arg(long, global = true, value_name = "FILE")]
+    pub freq: Option<PathBuf>,
+    /// Directory downloaded puzzles are kept in
+    #[arg(long, global = true, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+    /// Only accept solutions using these words, separated by commas
+    #[arg(long, global = true, value_name = "WORDS", value_delimiter = ',')]
+    pub require: Vec<String>,
+    /// Never use these words, separated by commas
+    #[arg(long, global = true, value_name = "WORDS", value_delimiter = ',')]
+    pub ban: Vec<String>,
+    /// Never use a word covering more than this many rows
+    #[arg(long, global = true, value_name = "N")]
+    pub max_rows: Option<usize>,
+    /// Only use words touching this edge of the board
+    #[arg(long, global = true, value_enum)]
+    pub touch_edge: Option<Edge>,
+    /// How to search for solutions
+    #[arg(long, global = true, value_enum, default_value_t = Engine::Backtrack)]
+    pub engine: Engine,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Colour when stdout is a terminal and NO_COLOR isn't set
+    Auto,
+    Always,
+    Never,
+}
+
+/// Where a puzzle comes from
+#[derive(Clone, Debug)]
+pub enum Source {
+    /// A puzzle id on the site
+    Id(usize),
+    /// A json puzzle, or a grid of letters
+    File(PathBuf),
+    /// A json puzzle, or a grid of letters, read from stdin
+    Stdin,
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Source, String> {
+        if s == "-" {
+            Ok(Source::Stdin)
+        } else if let Ok(id) = s.parse() {
+            Ok(Source::Id(id))
+        } else if s.is_empty() {
+            Err("expected a puzzle id, a file, or -".to_string())
+        } else {
+            Ok(Source::File(s.into()))
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Id(id) => write!(f, "puzzle {}", id),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+/// A board ready to be solved, along with the puzzle it came from, if any
+pub struct Loaded {
+    pub id: Option<usize>,
+    pub puzzle: Option<Puzzle>,
+    pub board: Board,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Find one solution to a puzzle
+    Solve {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        /// Record every step of the search to this file, see `replay`
+        #[arg(long, value_name = "FILE")]
+        trace: Option<PathBuf>,
+        #[command(flatten)]
+        given: GivenArgs,
+    },
+    /// Find every solution to a puzzle
+    Enumerate {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        /// Stop after this many solutions
+        #[arg(long)]
+        limit: Option<usize>,
+        #[command(flatten)]
+        given: GivenArgs,
+    },
+    /// Count the solutions to a puzzle
+    Count {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        #[command(flatten)]
+        given: GivenArgs,
+    },
+    /// Check that the official solution is legal and the only one
+    Verify {
+        /// Puzzle id, or file holding a json puzzle, or - for stdin
+        puzzle: Source,
+    },
+    /// Draw a solved board as an svg image
+    Svg {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        /// Draw the puzzle's own solution instead of solving it
+        #[arg(long)]
+        official: bool,
+        /// Draw the letters without solving
+        #[arg(long, conflicts_with = "official")]
+        unsolved: bool,
+        /// Only outline regions, without filling them in
+        #[arg(long)]
+        no_fill: bool,
+        /// Number the words in reading order
+        #[arg(long)]
+        numbers: bool,
+        /// Write the image here instead of to stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Solve a batch of puzzles and write a self contained html report
+    Report {
+        /// Puzzle ids, files holding json puzzles or letter grids, or - for stdin
+        puzzles: Vec<Source>,
+        /// Build the report from the output of `solve --format json`
+        /// instead of solving, one object per line
+        #[arg(long, value_name = "FILE")]
+        json: Vec<PathBuf>,
+        /// Heading for the page
+        #[arg(long, default_value = "Cell tower run")]
+        title: String,
+        /// Write the page here instead of to stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Show where two solutions to an ambiguous puzzle differ
+    Diff {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        /// Number of the first solution, counting from 1 in the order
+        /// `enumerate` finds them
+        #[arg(default_value_t = 1)]
+        first: usize,
+        /// Number of the second solution
+        #[arg(default_value_t = 2)]
+        second: usize,
+    },
+    /// List every word which could be traced on the board, from any cell
+    Words {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        /// Leave out words shorter than this
+        #[arg(long, default_value_t = 0)]
+        min: usize,
+    },
+    /// Write the puzzle as a satisfiability problem in DIMACS cnf format
+    Cnf {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        /// Solve the problem with the built in solver and check the answer
+        #[arg(long)]
+        check: bool,
+        /// Write the problem here instead of to stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        given: GivenArgs,
+    },
+    /// Read a sat solver's answer to `cnf` and check it's a solution
+    Sat {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        /// The solver's output, or - for stdin
+        assignment: PathBuf,
+        #[command(flatten)]
+        given: GivenArgs,
+    },
+    /// Show how many words could cover each free cell
+    Coverage {
+        /// Puzzle id, file holding a json puzzle or a letter grid, or - for stdin
+        puzzle: Source,
+        /// List the words that could cover each cell
+        #[arg(long)]
+        words: bool,
+        #[command(flatten)]
+        given: GivenArgs,
+    },
+    /// Solve puzzles with rules a person could follow, explaining each step
+    Deduce {
+        /// Puzzle ids, files holding json puzzles or letter grids, or
+        /// directories of them
+        #[arg(required = true)]
+        puzzles: Vec<Source>,
+        /// Only say whether each puzzle could be solved by logic alone
+        #[arg(long)]
+        brief: bool,
+    },
+    /// Sort puzzles from easiest to hardest
+    Rank {
+        /// Puzzle ids, files holding json puzzles or letter grids, or
+        /// directories of them
+        #[arg(required = true)]
+        puzzles: Vec<Source>,
+    },
+    /// Time the solver over a directory of puzzle files
+    Bench(BenchArgs),
+    /// Make a new puzzle with a unique solution, printed as json
+    Generate(GenerateArgs),
+    /// Download puzzles into the cache and print them
+    Fetch {
+        /// Puzzle ids
+        #[arg(required = true)]
+        ids: Vec<usize>,
+    },
+    /// Download a range of puzzles into the cache, several at a time
+    Mirror {
+        /// First puzzle id
+        first: usize,
+        /// Last puzzle id, inclusive
+        last: usize,
+        /// Number of downloads to run at once
+        #[arg(long, default_value_t = 8)]
+        jobs: usize,
+    },
+    /// Look words up in the dictionary
+    Dict {
+        /// Words to look up, prints the size of the dictionary if none are given
+        words: Vec<String>,
+        /// Print every word in the dictionary
+        #[arg(long, conflicts_with = "words")]
+        list: bool,
+    },
+    /// Step through a trace written by `solve --trace`
+    Replay {
+        /// Trace file
+        trace: PathBuf,
+    },
+    /// Solve a puzzle yourself in the terminal
+    Play {
+        /// Puzzle id, or file holding a json puzzle or a letter grid
+        puzzle: Source,
+    },
+    /// Read puzzle ids from stdin, solving each one
+    Repl,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Directory of json puzzles or letter grids
+    pub dir: PathBuf,
+    /// Time finding every solution as well as the first
+    #[arg(long)]
+    pub enumerate: bool,
+    /// Untimed runs of each puzzle before measuring
+    #[arg(long, default_value_t = 1)]
+    pub warmup: usize,
+    /// Timed runs of each puzzle
+    #[arg(long, default_value_t = 5)]
+    pub repeat: usize,
+    /// Compare against results saved with `--save`, failing on slowdowns
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+    /// Save the results, for use as a later baseline
+    #[arg(long, value_name = "FILE")]
+    pub save: Option<PathBuf>,
+    /// Percentage a median time may grow by before it counts as a slowdown
+    #[arg(long, default_value_t = 10.0)]
+    pub threshold: f64,
+    /// Also find every solution with both engines, failing if they differ
+    #[arg(long)]
+    pub check_engines: bool,
+}
+
+/// Words to place before searching, so it carries on from there
+#[derive(Args)]
+pub struct GivenArgs {
+    /// Words to place first, in reading order, separated by commas
+    #[arg(long, value_name = "WORDS", value_delimiter = ',')]
+    pub given: Vec<String>,
+    /// Json file of words to place first: a list of words and lists of
+    /// [x, y] cells, or the output of `solve --format json`
+    #[arg(long, value_name = "FILE", conflicts_with = "given")]
+    pub given_file: Option<PathBuf>,
+}
+
+impl GivenArgs {
+    /// Place the given words on the board, failing if they aren't a legal
+    /// start to it
+    pub fn place(&self, board: &mut Board) -> Result<(), Failure> {
+        let words = match &self.given_file {
+            Some(path) => {
+                let bad = |e: &dyn fmt::Display| {
+                    Failure::new(exit::ERROR, format!("{}: {}", path.display(), e))
+                };
+                let text = fs::read_to_string(path).map_err(|e| bad(&e))?;
+                let file: GivenFile = serde_json::from_str(&text).map_err(|e| bad(&e))?;
+                file.into_words()
+            }
+            None => self.given.iter().cloned().map(Given::Word).collect(),
+        };
+        given::place(board, &words).map_err(|e| Failure::new(exit::USAGE, e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Seed for the random choices, picked from the clock if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Shortest word to use
+    #[arg(long, default_value_t = 4)]
+    pub min: usize,
+    /// Longest word to use
+    #[arg(long, default_value_t = 8)]
+    pub max: usize,
+    /// Only keep puzzles rated in this band
+    #[arg(long)]
+    pub band: Option<Band>,
+    /// Words the puzzle must contain, separated by commas
+    #[arg(long, value_name = "WORDS", value_delimiter = ',')]
+    pub theme: Vec<String>,
+    /// Draw the other words only from this tier of the frequency list
+    #[arg(long)]
+    pub tier: Option<Tier>,
+    /// Make a generated puzzle again, from the recipe saved in it
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["seed", "min", "max", "band", "theme", "tier"]
+    )]
+    pub recipe: Option<PathBuf>,
+    /// Boards to try before giving up
+    #[arg(long, default_value_t = 500)]
+    pub attempts: usize,
+    /// Write the puzzle here instead of to stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// An error which ends the program, along with the exit code to use
+#[derive(Debug)]
+pub struct Failure {
+    pub code: u8,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new<S: Into<String>>(code: u8, message: S) -> Failure {
+        Failure {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<client::Error> for Failure {
+    fn from(e: client::Error) -> Failure {
+        Failure::new(exit::ERROR, e.to_string())
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Failure {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            // Whoever was reading our output has stopped, which isn't an error
+            Failure::new(exit::OK, "")
+        } else {
+            Failure::new(exit::ERROR, e.to_string())
+        }
+    }
+}
+
+impl From<dictionary::LoadError> for Failure {
+    fn from(e: dictionary::LoadError) -> Failure {
+        Failure::new(exit::ERROR, e.to_string())
+    }
+}
+
+impl From<PuzzleError> for Failure {
+    fn from(e: PuzzleError) -> Failure {
+        Failure::new(exit::ERROR, e.to_string())
+    }
+}
+
+impl From<TimedOut> for Failure {
+    fn from(_: TimedOut) -> Failure {
+        Failure::new(exit::TIMEOUT, "gave up after reaching the timeout")
+    }
+}
+
+/// State shared by every command
+pub struct Context {
+    pub format: Format,
+    pub render: Style,
+    pub dict: Dictionary,
+    pub freq: Frequencies,
+    pub timeout: Option<Duration>,
+    pub cache: Cache,
+    pub constraints: Constraints,
+    pub engine: Engine,
+    client: Option<Client>,
+}
+
+impl Context {
+    pub fn new(global: &GlobalArgs) -> Result<Context, Failure> {
+        let dict = match &global.dict {
+            Some(path) => Dictionary::load(path)?,
+            None => Dictionary::builtin(),
+        };
+        let freq = match &global.freq {
+            Some(path) => Frequencies::load(path)
+                .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", path.display(), e)))?,
+            None => Frequencies::default(),
+        };
+        let timeout = match global.timeout {
+            Some(secs) if secs.is_finite() && secs >= 0.0 => Some(Duration::from_secs_f64(secs)),
+            Some(_) => return Err(Failure::new(exit::USAGE, "timeout must be positive")),
+            None => None,
+        };
+        let mut constraints: Vec<Box<dyn Constraint>> = Vec::new();
+        for word in &global.require {
+            constraints.push(Box::new(Required(word.to_lowercase())));
+        }
+        for word in &global.ban {
+            constraints.push(Box::new(Banned(word.to_lowercase())));
+        }
+        match global.max_rows {
+            Some(0) => return Err(Failure::new(exit::USAGE, "max rows must be at least 1")),
+            Some(n) => constraints.push(Box::new(Shape::MaxRows(n))),
+            None => {}
+        }
+        if let Some(edge) = global.touch_edge {
+            constraints.push(Box::new(Shape::TouchEdge(edge)));
+        }
+        let cache = match &global.cache_dir {
+            Some(dir) => Cache::new(dir),
+            None => Cache::open_default(),
+        };
+        let color = apply_color(global.color);
+        let render = match global.render {
+            Some(style) => style,
+            None if color => Style::Color,
+            None => Style::Box,
+        };
+        Ok(Context {
+            format: global.format,
+            render,
+            dict,
+            freq,
+            timeout,
+            cache,
+            constraints: Constraints::new(constraints),
+            engine: global.engine,
+            client: None,
+        })
+    }
+
+    /// Draw a board in the chosen style
+    pub fn draw(&self, board: &Board) -> String {
+        render::render(board, self.render)
+    }
+
+    /// The point at which searches should give up, measured from now
+    pub fn deadline(&self) -> Option<Instant> {
+        self.timeout.map(|timeout| Instant::now() + timeout)
+    }
+
+    /// Rate how hard a board is, or `None` if it has no solution
+    pub fn rate(
+        &self,
+        board: &Board,
+        deadline: Option<Instant>,
+    ) -> Result<Option<Difficulty>, TimedOut> {
+        difficulty::rate(board, &self.freq, deadline)
+    }
+
+    /// The http client, built on first use
+    pub fn client(&mut self) -> Result<&Client, Failure> {
+        if self.client.is_none() {
+            self.client = Some(Client::from_env()?);
+        }
+        Ok(self.client.as_ref().unwrap())
+    }
+
+    /// Load a puzzle, preferring the local cache
+    pub fn puzzle(&mut self, id: usize) -> Result<Puzzle, Failure> {
+        if let Ok(Some(puzzle)) = self.cache.get(id) {
+            return Ok(puzzle);
+        }
+        let puzzle = Puzzle::fetch(self.client()?, id)?;
+        let _ = self.cache.put(id, &puzzle);
+        Ok(puzzle)
+    }
+
+    /// Build an unsolved board for the puzzle, using the chosen dictionary
+    pub fn board(&self, puzzle: Puzzle) -> Result<Board, PuzzleError> {
+        let mut board = Board::try_from(puzzle)?;
+        board.set_dictionary(self.dict);
+        board.set_constraints(self.constraints.clone());
+        Ok(board)
+    }
+
+    /// Load a board from an id, a file, or stdin
+    ///
+    /// Files may hold either a json puzzle or a plain grid of letters.
+    pub fn load(&mut self, source: &Source) -> Result<Loaded, Failure> {
+        let bad = |e: &dyn fmt::Display| Failure::new(exit::ERROR, format!("{}: {}", source, e));
+        let text = match source {
+            Source::Id(id) => {
+                let puzzle = self.puzzle(*id)?;
+                return Ok(Loaded {
+                    id: Some(*id),
+                    board: self.board(puzzle.clone()).map_err(|e| bad(&e))?,
+                    puzzle: Some(puzzle),
+                });
+            }
+            Source::File(path) => fs::read_to_string(path)
+                .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", source, e)))?,
+            Source::Stdin => {
+                let mut text = String::new();
+                io::stdin().read_to_string(&mut text)?;
+                text
+            }
+        };
+
+        if text.trim_start().starts_with('{') {
+            let puzzle: Puzzle = serde_json::from_str(&text)
+                .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", source, e)))?;
+            Ok(Loaded {
+                id: None,
+                board: self.board(puzzle.clone()).map_err(|e| bad(&e))?,
+                puzzle: Some(puzzle),
+            })
+        } else {
+            let mut board = Board::try_from(text.as_str())
+                .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", source, e)))?;
+            board.set_dictionary(self.dict);
+            board.set_constraints(self.constraints.clone());
+            Ok(Loaded {
+                id: None,
+                puzzle: None,
+                board,
+            })
+        }
+    }
+
+    fn reject_json(&self, command: &str) -> Result<(), Failure> {
+        match self.format {
+            Format::Json => Err(Failure::new(
+                exit::USAGE,
+                format!("json output isn't supported by {}", command),
+            )),
+            Format::Text => Ok(()),
+        }
+    }
+}
+
+/// Decide whether to colour output, honouring `NO_COLOR`
+///
+/// Returns true if colour is enabled.
+fn apply_color(choice: ColorChoice) -> bool {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && io::stdout().is_terminal()
+        }
+    };
+    colored::control::set_override(enabled);
+    enabled
+}
+
+/// Parse the command line and run it
+pub fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let res =
+        Context::new(&cli.global).and_then(|mut ctx| match cli.command.unwrap_or(Command::Repl) {
+            Command::Solve {
+                puzzle,
+                trace,
+                given,
+            } => solve(&mut ctx, &puzzle, trace, &given),
+            Command::Enumerate {
+                puzzle,
+                limit,
+                given,
+            } => enumerate(&mut ctx, &puzzle, limit, &given),
+            Command::Count { puzzle, given } => count(&mut ctx, &puzzle, &given),
+            Command::Verify { puzzle } => verify(&mut ctx, &puzzle),
+            Command::Svg {
+                puzzle,
+                official,
+                unsolved,
+                no_fill,
+                numbers,
+                output,
+            } => {
+                let options = SvgOptions {
+                    fill: !no_fill,
+                    numbers,
+                    ..SvgOptions::default()
+                };
+                svg(&mut ctx, &puzzle, official, unsolved, &options, output)
+            }
+            Command::Report {
+                puzzles,
+                json,
+                title,
+                output,
+            } => report(&mut ctx, &puzzles, &json, &title, output),
+            Command::Diff {
+                puzzle,
+                first,
+                second,
+            } => diff(&mut ctx, &puzzle, first, second),
+            Command::Words { puzzle, min } => words(&mut ctx, &puzzle, min),
+            Command::Cnf {
+                puzzle,
+                check,
+                output,
+                given,
+            } => cnf(&mut ctx, &puzzle, check, output, &given),
+            Command::Sat {
+                puzzle,
+                assignment,
+                given,
+            } => sat(&mut ctx, &puzzle, &assignment, &given),
+            Command::Coverage {
+                puzzle,
+                words,
+                given,
+            } => coverage(&mut ctx, &puzzle, words, &given),
+            Command::Deduce { puzzles, brief } => deduce(&mut ctx, &puzzles, brief),
+            Command::Rank { puzzles } => rank(&mut ctx, &puzzles),
+            Command::Bench(args) => bench(&mut ctx, &args),
+            Command::Generate(args) => generate(&ctx, &args),
+            Command::Fetch { ids } => fetch(&mut ctx, &ids),
+            Command::Mirror { first, last, jobs } => mirror(&mut ctx, first, last, jobs),
+            Command::Dict { words, list } => dict(&ctx, &words, list),
+            Command::Replay { trace } => replay(&ctx, &trace),
+            Command::Play { puzzle } => play(&mut ctx, &puzzle),
+            Command::Repl => repl(&mut ctx),
+        });
+    match res {
+        Ok(code) => ExitCode::from(code),
+        Err(failure) => {
+            if !failure.message.is_empty() {
+                eprintln!("error: {}", failure);
+            }
+            ExitCode::from(failure.code)
+        }
+    }
+}
+
+fn print_metadata(out: &mut impl Write, puzzle: Option<&Puzzle>) -> io::Result<()> {
+    for (label, value) in puzzle.iter().flat_map(|p| p.metadata()) {
+        writeln!(out, "{}: {}", label, value)?;
+    }
+    Ok(())
+}
+
+fn print_constraints(out: &mut impl Write, board: &Board) -> io::Result<()> {
+    for constraint in board.constraints().iter() {
+        writeln!(out, "Constraint: {}", constraint)?;
+    }
+    Ok(())
+}
+
+/// Print a value as a single line of json
+fn print_json<T: serde::Serialize>(value: &T) -> Result<(), Failure> {
+    let mut out = io::stdout().lock();
+    serde_json::to_writer(&mut out, value).map_err(io::Error::from)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn solve(
+    ctx: &mut Context,
+    source: &Source,
+    trace_path: Option<PathBuf>,
+    given: &GivenArgs,
+) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    let Loaded {
+        id,
+        puzzle,
+        mut board,
+    } = ctx.load(source)?;
+    given.place(&mut board)?;
+    if trace_path.is_some() && ctx.engine != Engine::Backtrack {
+        return Err(Failure::new(
+            exit::USAGE,
+            "only the backtrack engine can record a trace",
+        ));
+    }
+    if ctx.format == Format::Text {
+        print_metadata(&mut out, puzzle.as_ref())?;
+        print_constraints(&mut out, &board)?;
+    }
+    // The timeout covers rating the solution as well as finding it
+    let deadline = ctx.deadline();
+    let start = Instant::now();
+    let solved = match trace_path {
+        Some(path) => {
+            let bad =
+                |e: io::Error| Failure::new(exit::ERROR, format!("{}: {}", path.display(), e));
+            let file = fs::File::create(&path).map_err(bad)?;
+            let info = PuzzleInfo::new(id, puzzle.as_ref());
+            let mut trace =
+                TraceWriter::new(io::BufWriter::new(file), info, &board).map_err(bad)?;
+            let solved = board.solve_traced(deadline, &mut |frame| trace.frame(&frame));
+            // Keep the trace even if the search timed out, it may show why
+            trace.finish().map_err(bad)?;
+            solved?
+        }
+        None => ctx.engine.solve_until(&mut board, deadline)?,
+    };
+    let elapsed = start.elapsed();
+    match ctx.format {
+        Format::Text if solved => {
+            writeln!(out, "Solved board in {:?}", elapsed)?;
+            write!(out, "{}", ctx.draw(&board))?;
+            if let Some(difficulty) = ctx.rate(&board, deadline)? {
+                writeln!(out, "Difficulty: {}", difficulty)?;
+            }
+        }
+        Format::Text => writeln!(out, "No solution found after {:?}", elapsed)?,
+        Format::Json => {
+            let info = PuzzleInfo::new(id, puzzle.as_ref());
+            let mut report = SolveReport::new(info, puzzle.as_ref(), &board, solved, elapsed);
+            if solved {
+                report.difficulty = ctx.rate(&board, deadline)?;
+            }
+            print_json(&report)?;
+        }
+    }
+    Ok(if solved { exit::OK } else { exit::FAILED })
+}
+
+/// Print every solution as it is found
+///
+/// With `--format json` each solution is written as one line of json, so the
+/// output can be consumed while the search is still running.
+fn enumerate(
+    ctx: &mut Context,
+    source: &Source,
+    limit: Option<usize>,
+    given: &GivenArgs,
+) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    let Loaded {
+        id,
+        puzzle,
+        mut board,
+    } = ctx.load(source)?;
+    given.place(&mut board)?;
+    let format = ctx.format;
+    let style = ctx.render;
+    if format == Format::Text {
+        print_metadata(&mut out, puzzle.as_ref())?;
+        print_constraints(&mut out, &board)?;
+    }
+    let info = PuzzleInfo::new(id, puzzle.as_ref());
+    let limit = limit.unwrap_or(usize::MAX);
+    let deadline = ctx.deadline();
+    let start = Instant::now();
+    let mut n = 0;
+    let mut write_error = None;
+    let res = ctx
+        .engine
+        .enumerate_until(&mut board, deadline, &mut |solution| {
+            n += 1;
+            let written = match format {
+                Format::Text => writeln!(out, "Solution {}", n)
+                    .and_then(|()| write!(out, "{}", render::render(solution, style)))
+                    .map_err(Failure::from),
+                Format::Json => print_json(&SolutionLine {
+                    puzzle: info.clone(),
+                    index: n,
+                    elapsed_ms: millis(start.elapsed()),
+                    stats: solution.stats(),
+                    words: solution.words(),
+                }),
+            };
+            if let Err(e) = written {
+                write_error = Some(e);
+                ControlFlow::Break(())
+            } else if n >= limit {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+    if let Some(e) = write_error {
+        return Err(e);
+    }
+    if format == Format::Text {
+        writeln!(out, "Found {} solutions", n)?;
+    }
+    res?;
+    Ok(if n > 0 { exit::OK } else { exit::FAILED })
+}
+
+fn count(ctx: &mut Context, source: &Source, given: &GivenArgs) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    let Loaded { id, mut board, .. } = ctx.load(source)?;
+    given.place(&mut board)?;
+    let mut n = 0usize;
+    ctx.engine
+        .enumerate_until(&mut board, ctx.deadline(), &mut |_| {
+            n += 1;
+            ControlFlow::Continue(())
+        })?;
+    match ctx.format {
+        Format::Text => writeln!(out, "{}", n)?,
+        Format::Json => writeln!(out, "{}", json!({ "id": id, "solutions": n }))?,
+    }
+    Ok(if n > 0 { exit::OK } else { exit::FAILED })
+}
+
+fn verify(ctx: &mut Context, source: &Source) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    let (id, puzzle, mut board) = match ctx.load(source)? {
+        Loaded {
+            id,
+            puzzle: Some(puzzle),
+            board,
+        } => (id, puzzle, board),
+        _ => {
+            return Err(Failure::new(
+                exit::USAGE,
+                "verify needs a puzzle with an official solution, not a letter grid",
+            ))
+        }
+    };
+    let official = Board::official_solution(&puzzle)?;
+    let regions = puzzle.official_regions();
+
+    let legal = board.check_words(&regions);
+    let mut n = 0;
+    let mut found = false;
+    ctx.engine
+        .enumerate_until(&mut board, ctx.deadline(), &mut |s| {
+            n += 1;
+            found |= s.same_solution(&official);
+            ControlFlow::Continue(())
+        })?;
+    let unique = n == 1;
+    let ok = legal.is_ok() && found && unique;
+
+    match ctx.format {
+        Format::Text => {
+            match legal {
+                Ok(()) => writeln!(out, "Official solution is legal")?,
+                Err(i) => writeln!(out, "Official solution is illegal at word {}", i + 1)?,
+            }
+            writeln!(
+                out,
+                "Solver {} the official solution",
+                if found { "finds" } else { "doesn't find" }
+            )?;
+            writeln!(out, "Puzzle has {} solutions", n)?;
+        }
+        Format::Json => writeln!(
+            out,
+            "{}",
+            json!({
+                "id": id,
+                "legal": legal.is_ok(),
+                "illegal_word": legal.err(),
+                "found": found,
+                "solutions": n,
+                "unique": unique,
+                "ok": ok,
+            })
+        )?,
+    }
+    Ok(if ok { exit::OK } else { exit::FAILED })
+}
+
+fn svg(
+    ctx: &mut Context,
+    source: &Source,
+    official: bool,
+    unsolved: bool,
+    options: &SvgOptions,
+    output: Option<PathBuf>,
+) -> Result<u8, Failure> {
+    ctx.reject_json("svg")?;
+    let Loaded {
+        puzzle, mut board, ..
+    } = ctx.load(source)?;
+    let mut code = exit::OK;
+    if official {
+        match &puzzle {
+            Some(puzzle) => board = Board::official_solution(puzzle)?,
+            None => {
+                return Err(Failure::new(
+                    exit::USAGE,
+                    "--official needs a puzzle with an official solution, not a letter grid",
+                ))
+            }
+        }
+    } else if !unsolved && !ctx.engine.solve_until(&mut board, ctx.deadline())? {
+        eprintln!("No solution found, drawing the empty board");
+        code = exit::FAILED;
+    }
+
+    let image = svg::render(&board, options);
+    match output {
+        Some(path) => fs::write(path, image)?,
+        None => io::stdout().lock().write_all(image.as_bytes())?,
+    }
+    Ok(code)
+}
+
+fn report(
+    ctx: &mut Context,
+    sources: &[Source],
+    json: &[PathBuf],
+    title: &str,
+    output: Option<PathBuf>,
+) -> Result<u8, Failure> {
+    ctx.reject_json("report")?;
+    if sources.is_empty() && json.is_empty() {
+        return Err(Failure::new(
+            exit::USAGE,
+            "give some puzzles to solve, or --json files to read",
+        ));
+    }
+
+    let mut reports = Vec::new();
+    for path in json {
+        let text = fs::read_to_string(path)
+            .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", path.display(), e)))?;
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let report: SolveReport = serde_json::from_str(line).map_err(|e| {
+                Failure::new(
+                    exit::ERROR,
+                    format!("{}, line {}: {}", path.display(), i + 1, e),
+                )
+            })?;
+            reports.push(report);
+        }
+    }
+    for source in sources {
+        let Loaded {
+            id,
+            puzzle,
+            mut board,
+        } = ctx.load(source)?;
+        let info = PuzzleInfo::new(id, puzzle.as_ref());
+        let deadline = ctx.deadline();
+        let start = Instant::now();
+        let res = ctx.engine.solve_until(&mut board, deadline);
+        let mut report = SolveReport::new(
+            info,
+            puzzle.as_ref(),
+            &board,
+            res == Ok(true),
+            start.elapsed(),
+        );
+        report.timed_out = res.is_err();
+        if report.solved {
+            match ctx.rate(&board, deadline) {
+                Ok(difficulty) => report.difficulty = difficulty,
+                Err(TimedOut) => report.timed_out = true,
+            }
+        }
+        eprintln!(
+            "{}: {}",
+            source,
+            if report.solved { "solved" } else { "unsolved" }
+        );
+        reports.push(report);
+    }
+
+    let page = html::render(title, &reports);
+    match output {
+        Some(path) => fs::write(path, page)?,
+        None => io::stdout().lock().write_all(page.as_bytes())?,
+    }
+    let all_solved = reports.iter().all(|r| r.solved);
+    Ok(if all_solved { exit::OK } else { exit::FAILED })
+}
+
+fn diff(ctx: &mut Context, source: &Source, first: usize, second: usize) -> Result<u8, Failure> {
+    if first == 0 || second == 0 {
+        return Err(Failure::new(exit::USAGE, "solutions are numbered from 1"));
+    }
+    let Loaded { id, mut board, .. } = ctx.load(source)?;
+    let wanted = first.max(second);
+    let mut solutions = Vec::new();
+    ctx.engine
+        .enumerate_until(&mut board, ctx.deadline(), &mut |solution| {
+            solutions.push(solution.clone());
+            if solutions.len() >= wanted {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })?;
+    if solutions.len() < wanted {
+        return Err(Failure::new(
+            exit::FAILED,
+            format!("{} has only {} solutions", source, solutions.len()),
+        ));
+    }
+    let (left, right) = (&solutions[first - 1], &solutions[second - 1]);
+    let comparison = Comparison::of(left, right);
+    match ctx.format {
+        Format::Text => {
+            let mut out = io::stdout().lock();
+            writeln!(
+                out,
+                "Solution {} vs solution {}: {} words in common, {} differences",
+                first,
+                second,
+                comparison.shared.len(),
+                comparison.differences.len()
+            )?;
+            for difference in &comparison.differences {
+                writeln!(out, "  {}", difference)?;
+            }
+            write!(
+                out,
+                "{}",
+                diff::side_by_side(left, right, &comparison, ctx.render)
+            )?;
+        }
+        Format::Json => print_json(&json!({
+            "id": id,
+            "first": first,
+            "second": second,
+            "shared": comparison.shared,
+            "differences": comparison.differences,
+        }))?,
+    }
+    Ok(exit::OK)
+}
+
+fn words(ctx: &mut Context, source: &Source, min: usize) -> Result<u8, Failure> {
+    let Loaded { id, board, .. } = ctx.load(source)?;
+    let groups = traceable::traceable(&board, min);
+    match ctx.format {
+        Format::Text => {
+            let mut out = io::stdout().lock();
+            for group in &groups {
+                writeln!(
+                    out,
+                    "{} {}: {} words",
+                    group.start,
+                    group.letter.to_ascii_uppercase(),
+                    group.count()
+                )?;
+                for length in &group.lengths {
+                    // A word traced several ways is listed once, with a count
+                    let mut listed: Vec<String> = Vec::new();
+                    for (i, traced) in length.words.iter().enumerate() {
+                        if i > 0 && length.words[i - 1].word == traced.word {
+                            continue;
+                        }
+                        let ways = length.words[i..]
+                            .iter()
+                            .take_while(|t| t.word == traced.word)
+                            .count();
+                        listed.push(if ways > 1 {
+                            format!("{} ({} ways)", traced.word, ways)
+                        } else {
+                            traced.word.clone()
+                        });
+                    }
+                    writeln!(out, "  {}: {}", length.length, listed.join(", "))?;
+                }
+            }
+            let total: usize = groups.iter().map(|g| g.count()).sum();
+            writeln!(out, "{} words from {} cells", total, groups.len())?;
+        }
+        Format::Json => print_json(&json!({ "id": id, "starts": groups }))?,
+    }
+    Ok(if groups.is_empty() {
+        exit::FAILED
+    } else {
+        exit::OK
+    })
+}
+
+fn cnf(
+    ctx: &mut Context,
+    source: &Source,
+    check: bool,
+    output: Option<PathBuf>,
+    given: &GivenArgs,
+) -> Result<u8, Failure> {
+    ctx.reject_json("cnf")?;
+    let Loaded { mut board, .. } = ctx.load(source)?;
+    given.place(&mut board)?;
+    let cnf = Cnf::of(&board);
+    match &output {
+        Some(path) => {
+            let file = fs::File::create(path)
+                .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", path.display(), e)))?;
+            let mut out = io::BufWriter::new(file);
+            cnf.write(&board, &mut out)?;
+            out.flush()?;
+        }
+        None => cnf.write(&board, io::BufWriter::new(io::stdout().lock()))?,
+    }
+    eprintln!(
+        "{} variables, {} clauses",
+        cnf.variables(),
+        cnf.clauses.len()
+    );
+    if !check {
+        return Ok(exit::OK);
+    }
+    // A solution the constraints rule out doesn't count, so block it and
+    // ask again
+    let mut cnf = cnf;
+    loop {
+        let Some(values) = cnf.solve() else {
+            eprintln!("Unsatisfiable");
+            return Ok(exit::FAILED);
+        };
+        match cnf.solution(&board, &values) {
+            Ok(solved) => {
+                eprintln!("Satisfiable, and the assignment is a solution:");
+                eprint!("{}", ctx.draw(&solved));
+                return Ok(exit::OK);
+            }
+            Err(CnfError::Constrained) => {
+                let blocking = (1..=cnf.variables() as i32)
+                    .filter(|&v| values[v as usize - 1])
+                    .map(|v| -v)
+                    .collect();
+                cnf.clauses.push(blocking);
+            }
+            Err(e) => {
+                return Err(Failure::new(
+                    exit::FAILED,
+                    format!("the built in solver's answer is wrong: {}", e),
+                ))
+            }
+        }
+    }
+}
+
+fn sat(
+    ctx: &mut Context,
+    source: &Source,
+    assignment: &Path,
+    given: &GivenArgs,
+) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    let Loaded { id, mut board, .. } = ctx.load(source)?;
+    given.place(&mut board)?;
+    let text = if assignment == Path::new("-") {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        fs::read_to_string(assignment)
+            .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", assignment.display(), e)))?
+    };
+    let cnf = Cnf::of(&board);
+    let res = cnf
+        .parse_assignment(&text)
+        .and_then(|values| cnf.solution(&board, &values));
+    let solved = match res {
+        Ok(solved) => solved,
+        Err(e @ (CnfError::Parse { .. } | CnfError::NoSuchVariable { .. })) => {
+            return Err(Failure::new(
+                exit::ERROR,
+                format!("{}: {}", assignment.display(), e),
+            ))
+        }
+        Err(e) => {
+            match ctx.format {
+                Format::Text => writeln!(out, "The assignment isn't a solution: {}", e)?,
+                Format::Json => writeln!(
+                    out,
+                    "{}",
+                    json!({ "id": id, "solution": false, "error": e.to_string() })
+                )?,
+            }
+            return Ok(exit::FAILED);
+        }
+    };
+    match ctx.format {
+        Format::Text => {
+            writeln!(out, "The assignment is a solution")?;
+            write!(out, "{}", ctx.draw(&solved))?;
+        }
+        Format::Json => writeln!(
+            out,
+            "{}",
+            json!({ "id": id, "solution": true, "words": solved.words() })
+        )?,
+    }
+    Ok(exit::OK)
+}
+
+fn coverage(
+    ctx: &mut Context,
+    source: &Source,
+    words: bool,
+    given: &GivenArgs,
+) -> Result<u8, Failure> {
+    let Loaded { id, mut board, .. } = ctx.load(source)?;
+    given.place(&mut board)?;
+    let coverage = Coverage::of(&board);
+    match ctx.format {
+        Format::Text => {
+            let mut out = io::stdout().lock();
+            write!(out, "{}", coverage::heatmap(&board, &coverage, ctx.render))?;
+            for cell in coverage.stranded() {
+                writeln!(out, "Cell {} can't be covered by any word", cell.cell)?;
+            }
+            if words {
+                for cell in &coverage.cells {
+                    writeln!(
+                        out,
+                        "{} {}: {}",
+                        cell.cell,
+                        cell.letter.to_ascii_uppercase(),
+                        cell.words.join(", ")
+                    )?;
+                }
+            }
+        }
+        Format::Json => print_json(&json!({ "id": id, "cells": coverage.cells }))?,
+    }
+    Ok(if coverage.stranded().next().is_none() {
+        exit::OK
+    } else {
+        exit::FAILED
+    })
+}
+
+/// Expand directories into the puzzles inside them
+fn expand_sources(sources: &[Source]) -> Result<Vec<Source>, Failure> {
+    let mut expanded = Vec::new();
+    for source in sources {
+        match source {
+            Source::File(path) if path.is_dir() => {
+                let files = puzzle_files(path)
+                    .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", source, e)))?;
+                expanded.extend(files.into_iter().map(Source::File));
+            }
+            _ => expanded.push(source.clone()),
+        }
+    }
+    Ok(expanded)
+}
+
+fn deduce(ctx: &mut Context, sources: &[Source], brief: bool) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    let expanded = expand_sources(sources)?;
+    let mut logic_only = 0;
+    let mut all_solved = true;
+    for source in &expanded {
+        let Loaded { id, mut board, .. } = ctx.load(source)?;
+        let deduction = deduce::deduce(&mut board, ctx.deadline())?;
+        all_solved &= deduction.solved;
+        if deduction.logic_only {
+            logic_only += 1;
+        }
+        let verdict = if deduction.logic_only {
+            format!("solved by logic alone, {} words", deduction.by_logic)
+        } else if deduction.solved {
+            format!(
+                "logic placed {} words, search found the rest",
+                deduction.by_logic
+            )
+        } else {
+            "no solution".to_string()
+        };
+        match ctx.format {
+            Format::Text if brief => writeln!(out, "{}: {}", source, verdict)?,
+            Format::Text => {
+                writeln!(out, "{}:", source)?;
+                for (i, step) in deduction.steps.iter().enumerate() {
+                    writeln!(out, "{:>4}. {}", i + 1, step)?;
+                }
+                writeln!(out, "{}", verdict)?;
+                if deduction.solved {
+                    write!(out, "{}", ctx.draw(&board))?;
+                }
+            }
+            Format::Json => print_json(&json!({
+                "puzzle": source.to_string(),
+                "id": id,
+                "deduction": deduction,
+            }))?,
+        }
+    }
+    if ctx.format == Format::Text && expanded.len() > 1 {
+        writeln!(
+            out,
+            "{} of {} puzzles can be solved by logic alone",
+            logic_only,
+            expanded.len()
+        )?;
+    }
+    Ok(if all_solved { exit::OK } else { exit::FAILED })
+}
+
+fn rank(ctx: &mut Context, sources: &[Source]) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    let expanded = expand_sources(sources)?;
+
+    let mut rated = Vec::new();
+    let mut unrated = Vec::new();
+    let mut skipped = 0;
+    for source in &expanded {
+        let loaded = match ctx.load(source) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("skipping {}", e);
+                skipped += 1;
+                continue;
+            }
+        };
+        match ctx.rate(&loaded.board, ctx.deadline()) {
+            Ok(Some(difficulty)) => rated.push((source, loaded.id, difficulty)),
+            Ok(None) => unrated.push((source, loaded.id, false)),
+            Err(TimedOut) => unrated.push((source, loaded.id, true)),
+        }
+    }
+    rated.sort_by(|a, b| a.2.score.total_cmp(&b.2.score));
+
+    match ctx.format {
+        Format::Text => {
+            writeln!(out, "{:>4} {:>6}  {:<9} puzzle", "rank", "score", "band")?;
+            for (i, (source, _, difficulty)) in rated.iter().enumerate() {
+                writeln!(
+                    out,
+                    "{:>4} {:>6.1}  {:<9} {}",
+                    i + 1,
+                    difficulty.score,
+                    difficulty.band.to_string(),
+                    source
+                )?;
+            }
+            for (source, _, timed_out) in &unrated {
+                let status = if *timed_out { "timed out" } else { "unsolved" };
+                writeln!(out, "{:>4} {:>6}  {:<9} {}", "-", "-", status, source)?;
+            }
+        }
+        Format::Json => {
+            for (i, (source, id, difficulty)) in rated.iter().enumerate() {
+                print_json(&json!({
+                    "rank": i + 1,
+                    "puzzle": source.to_string(),
+                    "id": id,
+                    "difficulty": difficulty,
+                }))?;
+            }
+            for (source, id, timed_out) in &unrated {
+                print_json(&json!({
+                    "rank": null,
+                    "puzzle": source.to_string(),
+                    "id": id,
+                    "timed_out": timed_out,
+                }))?;
+            }
+        }
+    }
+    Ok(if unrated.is_empty() && skipped == 0 {
+        exit::OK
+    } else {
+        exit::FAILED
+    })
+}
+
+/// List the files in a directory, in name order, skipping hidden ones
+fn puzzle_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Run a search `warmup + repeat` times on fresh copies of the board,
+/// keeping the time and node count of each timed run
+fn time_runs<T>(
+    ctx: &Context,
+    board: &Board,
+    args: &BenchArgs,
+    search: impl Fn(&mut Board, Option<Instant>) -> Result<T, TimedOut>,
+) -> Result<(T, Summary), Failure> {
+    let mut runs = Vec::with_capacity(args.repeat);
+    let mut result = None;
+    for i in 0..args.warmup + args.repeat {
+        let mut board = board.clone();
+        board.reset_stats();
+        let start = Instant::now();
+        result = Some(search(&mut board, ctx.deadline())?);
+        if i >= args.warmup {
+            runs.push((millis(start.elapsed()), board.stats().nodes));
+        }
+    }
+    Ok((result.unwrap(), Summary::of(&runs)))
+}
+
+fn bench(ctx: &mut Context, args: &BenchArgs) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    if args.repeat == 0 {
+        return Err(Failure::new(exit::USAGE, "repeat must be at least 1"));
+    }
+    let in_dir =
+        |e: &dyn fmt::Display| Failure::new(exit::ERROR, format!("{}: {}", args.dir.display(), e));
+    let baseline: Option<BenchReport> = match &args.baseline {
+        Some(path) => {
+            let text = fs::read_to_string(path)
+                .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", path.display(), e)))?;
+            Some(
+                serde_json::from_str(&text)
+                    .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", path.display(), e)))?,
+            )
+        }
+        None => None,
+    };
+
+    let mut report = BenchReport {
+        warmup: args.warmup,
+        repeat: args.repeat,
+        puzzles: Vec::new(),
+    };
+    let mut disagreements = Vec::new();
+    for path in puzzle_files(&args.dir).map_err(|e| in_dir(&e))? {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let board = match ctx.load(&Source::File(path)) {
+            Ok(loaded) => loaded.board,
+            Err(e) => {
+                eprintln!("skipping {}", e);
+                continue;
+            }
+        };
+        let engine = ctx.engine;
+        let (solved, solve) = time_runs(ctx, &board, args, |board, deadline| {
+            engine.solve_until(board, deadline)
+        })?;
+        let (solutions, enumerate) = if args.enumerate {
+            let (n, summary) = time_runs(ctx, &board, args, |board, deadline| {
+                let mut n = 0;
+                engine.enumerate_until(board, deadline, &mut |_| {
+                    n += 1;
+                    ControlFlow::Continue(())
+                })?;
+                Ok(n)
+            })?;
+            (Some(n), Some(summary))
+        } else {
+            (None, None)
+        };
+        if args.check_engines {
+            let agreement = dlx::crosscheck(&board, ctx.deadline())?;
+            if !agreement.agree {
+                disagreements.push((name.clone(), agreement));
+            }
+        }
+        report.puzzles.push(PuzzleBench {
+            name,
+            solved,
+            solve,
+            enumerate,
+            solutions,
+        });
+    }
+    if report.puzzles.is_empty() {
+        return Err(in_dir(&"no puzzles found"));
+    }
+
+    let regressions = match &baseline {
+        Some(baseline) => bench::compare(baseline, &report, args.threshold),
+        None => Vec::new(),
+    };
+    if let Some(path) = &args.save {
+        let text = serde_json::to_string_pretty(&report).map_err(io::Error::from)?;
+        fs::write(path, text + "\n")
+            .map_err(|e| Failure::new(exit::ERROR, format!("{}: {}", path.display(), e)))?;
+    }
+
+    match ctx.format {
+        Format::Text => {
+            writeln!(
+                out,
+                "{} puzzles, {} warm up and {} timed runs each",
+                report.puzzles.len(),
+                args.warmup,
+                args.repeat
+            )?;
+            writeln!(
+                out,
+                "{:<24} {:>10} {:>10} {:>10} {:>10}",
+                "puzzle", "median ms", "p95 ms", "max ms", "nodes"
+            )?;
+            let mut row = |name: &str, s: &Summary| {
+                writeln!(
+                    out,
+                    "{:<24} {:>10.2} {:>10.2} {:>10.2} {:>10}",
+                    name, s.time_ms.median, s.time_ms.p95, s.time_ms.max, s.nodes.median
+                )
+            };
+            for puzzle in &report.puzzles {
+                row(&puzzle.name, &puzzle.solve)?;
+                if let Some(enumerate) = &puzzle.enumerate {
+                    let label = format!("  all {} solutions", puzzle.solutions.unwrap_or(0));
+                    row(&label, enumerate)?;
+                }
+            }
+            let all = report.aggregate();
+            writeln!(out)?;
+            writeln!(
+                out,
+                "solve, across puzzles: median {:.2} ms, p95 {:.2} ms, max {:.2} ms, nodes median {} p95 {} max {}",
+                all.time_ms.median, all.time_ms.p95, all.time_ms.max, all.nodes.median, all.nodes.p95, all.nodes.max
+            )?;
+            if let Some(all) = report.aggregate_enumerate() {
+                writeln!(
+                    out,
+                    "enumerate, across puzzles: median {:.2} ms, p95 {:.2} ms, max {:.2} ms, nodes median {} p95 {} max {}",
+                    all.time_ms.median, all.time_ms.p95, all.time_ms.max, all.nodes.median, all.nodes.p95, all.nodes.max
+                )?;
+            }
+            if baseline.is_some() {
+                if regressions.is_empty() {
+                    writeln!(out, "No slowdowns compared to the baseline")?;
+                } else {
+                    writeln!(
+                        out,
+                        "{} slowdowns compared to the baseline:",
+                        regressions.len()
+                    )?;
+                    for regression in &regressions {
+                        writeln!(out, "  {}", regression)?;
+                    }
+                }
+            }
+            if args.check_engines {
+                if disagreements.is_empty() {
+                    writeln!(out, "Both engines found the same solutions to every puzzle")?;
+                } else {
+                    writeln!(
+                        out,
+                        "{} puzzles where the engines disagree:",
+                        disagreements.len()
+                    )?;
+                    for (name, agreement) in &disagreements {
+                        writeln!(
+                            out,
+                            "  {}: backtrack found {} solutions, dlx {}",
+                            name, agreement.backtrack, agreement.dlx
+                        )?;
+                    }
+                }
+            }
+        }
+        Format::Json => print_json(&json!({
+            "report": report,
+            "solve": report.aggregate(),
+            "enumerate": report.aggregate_enumerate(),
+            "regressions": regressions,
+            "disagreements": disagreements
+                .iter()
+                .map(|(name, agreement)| json!({ "name": name, "agreement": agreement }))
+                .collect::<Vec<_>>(),
+        }))?,
+    }
+    Ok(if regressions.is_empty() && disagreements.is_empty() {
+        exit::OK
+    } else {
+        exit::FAILED
+    })
+}
+
+fn fetch(ctx: &mut Context, ids: &[usize]) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    for &id in ids {
+        let puzzle = ctx.puzzle(id)?;
+        match ctx.format {
+            Format::Text => {
+                writeln!(out, "Puzzle {} ({})", id, ctx.cache.path(id).display())?;
+                print_metadata(&mut out, Some(&puzzle))?;
+            }
+            Format::Json => writeln!(
+                out,
+                "{}",
+                serde_json::to_string(&puzzle).map_err(io::Error::from)?
+            )?,
+        }
+    }
+    Ok(exit::OK)
+}
+
+/// Download a range of puzzles into the local cache
+fn mirror(ctx: &mut Context, first: usize, last: usize, jobs: usize) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    ctx.reject_json("mirror")?;
+    let ids: Vec<usize> = (first..=last)
+        .filter(|&id| !ctx.cache.contains(id))
+        .collect();
+    writeln!(
+        out,
+        "Mirroring {} puzzles into {}",
+        ids.len(),
+        ctx.cache.dir().display()
+    )?;
+    ctx.client()?;
+    let client = ctx.client.as_ref().unwrap();
+
+    let start = Instant::now();
+    let mut failed = 0;
+    for (id, res) in futures_lite::stream::block_on(Puzzle::fetch_many(client, ids, jobs)) {
+        match res.map(|puzzle| ctx.cache.put(id, &puzzle)) {
+            Ok(Ok(())) => writeln!(out, "{}: ok", id)?,
+            Ok(Err(e)) => {
+                failed += 1;
+                writeln!(out, "{}: couldn't store: {}", id, e)?;
+            }
+            Err(e) => {
+                failed += 1;
+                writeln!(out, "{}: {}", id, e)?;
+            }
+        }
+    }
+    writeln!(out, "Finished in {:?}, {} failed", start.elapsed(), failed)?;
+    Ok(if failed > 0 { exit::ERROR } else { exit::OK })
+}
+
+fn dict(ctx: &Context, words: &[String], list: bool) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    if list {
+        for word in ctx.dict.words() {
+            writeln!(out, "{}", word)?;
+        }
+        return Ok(exit::OK);
+    }
+    if words.is_empty() {
+        match ctx.format {
+            Format::Text => writeln!(out, "{} words", ctx.dict.len())?,
+            Format::Json => writeln!(out, "{}", json!({ "words": ctx.dict.len() }))?,
+        }
+        return Ok(exit::OK);
+    }
+
+    let mut all_found = true;
+    for word in words {
+        let found = ctx.dict.is_word(word.to_ascii_lowercase().as_bytes());
+        all_found &= found;
+        match ctx.format {
+            Format::Text => writeln!(out, "{}: {}", word, if found { "yes" } else { "no" })?,
+            Format::Json => writeln!(out, "{}", json!({ "word": word, "found": found }))?,
+        }
+    }
+    Ok(if all_found { exit::OK } else { exit::FAILED })
+}
+
+fn generate(ctx: &Context, args: &GenerateArgs) -> Result<u8, Failure> {
+    let recipe = match &args.recipe {
+        Some(path) => {
+            let bad = |e: &dyn fmt::Display| {
+                Failure::new(exit::ERROR, format!("{}: {}", path.display(), e))
+            };
+            let text = fs::read_to_string(path).map_err(|e| bad(&e))?;
+            let puzzle: Puzzle = serde_json::from_str(&text).map_err(|e| bad(&e))?;
+            Recipe::of(&puzzle).ok_or_else(|| bad(&"not a generated puzzle"))?
+        }
+        None => Recipe {
+            min_size: args.min,
+            max_size: args.max,
+            band: args.band,
+            theme: args.theme.clone(),
+            tier: args.tier,
+            ..Recipe::new(args.seed.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos() as u64)
+            }))
+        },
+    };
+    let options = generate::Options {
+        attempts: args.attempts,
+        deadline: ctx.deadline(),
+        ..generate::Options::default()
+    };
+    let generated = match generate::generate(ctx.dict, &ctx.freq, &recipe, &options) {
+        Ok(generated) => generated,
+        Err(GenerateError::TimedOut) => return Err(TimedOut.into()),
+        Err(e @ GenerateError::GaveUp { .. }) => {
+            return Err(Failure::new(exit::FAILED, e.to_string()))
+        }
+        Err(e) => return Err(Failure::new(exit::USAGE, e.to_string())),
+    };
+    eprintln!(
+        "Generated from seed {} after {} attempts",
+        generated.recipe.seed, generated.attempts
+    );
+    if let Some(difficulty) = &generated.difficulty {
+        eprintln!("Difficulty: {}", difficulty);
+    }
+    let json = serde_json::to_string_pretty(&generated.puzzle).map_err(io::Error::from)?;
+    match &args.output {
+        Some(path) => fs::write(path, json + "\n")?,
+        None => writeln!(io::stdout().lock(), "{}", json)?,
+    }
+    Ok(exit::OK)
+}
+
+fn replay(ctx: &Context, path: &PathBuf) -> Result<u8, Failure> {
+    ctx.reject_json("replay")?;
+    let bad =
+        |e: &dyn fmt::Display| Failure::new(exit::ERROR, format!("{}: {}", path.display(), e));
+    let file = fs::File::open(path).map_err(|e| bad(&e))?;
+    let trace = Trace::read(io::BufReader::new(file)).map_err(|e| bad(&e))?;
+    let mut board = trace.board().map_err(|e| bad(&e))?;
+    board.set_dictionary(ctx.dict);
+    trace::replay(&trace, board, ctx.render)?;
+    Ok(exit::OK)
+}
+
+fn play(ctx: &mut Context, source: &Source) -> Result<u8, Failure> {
+    ctx.reject_json("play")?;
+    if matches!(source, Source::Stdin) {
+        return Err(Failure::new(
+            exit::USAGE,
+            "play reads keys from stdin, so can't load a puzzle from it",
+        ));
+    }
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(Failure::new(exit::USAGE, "play needs a terminal"));
+    }
+    let Loaded { board, .. } = ctx.load(source)?;
+    let mut game = Game::new(board);
+    let solved = play::run(&mut game, ctx.render, ctx.timeout)?;
+    if solved {
+        let mut out = io::stdout().lock();
+        writeln!(out, "Solved with {} words", game.n_placed())?;
+        write!(out, "{}", ctx.draw(game.board()))?;
+    }
+    Ok(exit::OK)
+}
+
+fn repl(ctx: &mut Context) -> Result<u8, Failure> {
+    let mut out = io::stdout().lock();
+    ctx.reject_json("repl")?;
+    let mut buffer = String::new();
+    // The board being hinted at, with the words given away so far
+    let mut hinting: Option<(Board, Hints)> = None;
+    writeln!(out, "Cell tower solver terminal")?;
+    writeln!(out, "Enter a puzzle id, and it will be loaded and solved")?;
+    writeln!(
+        out,
+        "Enter hint and an id for a nudge instead, then hint again for more"
+    )?;
+    loop {
+        buffer.clear();
+        if io::stdin().read_line(&mut buffer)? == 0 {
+            return Ok(exit::OK);
+        }
+        let line = buffer.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("hint") {
+            let rest = rest.trim();
+            if !rest.is_empty() {
+                let Ok(id) = rest.parse::<usize>() else {
+                    writeln!(out, "{} is not a valid id", rest)?;
+                    continue;
+                };
+                match ctx.puzzle(id).and_then(|puzzle| Ok(ctx.board(puzzle)?)) {
+                    Ok(board) => hinting = Some((board, Hints::new())),
+                    Err(e) => {
+                        writeln!(out, "Couldn't load puzzle {}: {}", id, e)?;
+                        continue;
+                    }
+                }
+            }
+            let Some((board, hints)) = &mut hinting else {
+                writeln!(out, "Enter hint and an id to pick a puzzle first")?;
+                continue;
+            };
+            // Once a word has been given away, move on to the next one
+            if let Some((hint, Level::Word)) = hints.current() {
+                board.insert_word(&hint.points.clone());
+                hints.reset();
+            }
+            match hints.next(board, ctx.deadline()) {
+                Ok(Some((hint, level))) => writeln!(out, "{}", hint.describe(level))?,
+                Ok(None) if board.is_done() => {
+                    writeln!(out, "That's every word")?;
+                    write!(out, "{}", ctx.draw(board))?;
+                }
+                Ok(None) => writeln!(out, "No solution found")?,
+                Err(e) => writeln!(out, "{}", Failure::from(e))?,
+            }
+        } else if let Ok(id) = line.parse::<usize>() {
+            writeln!(out, "Loading puzzle {}", id)?;
+            let loaded = ctx
+                .puzzle(id)
+                .and_then(|puzzle| Ok((ctx.board(puzzle.clone())?, puzzle)));
+            let (mut board, puzzle) = match loaded {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    writeln!(out, "Couldn't load puzzle {}: {}", id, e)?;
+                    continue;
+                }
+            };
+            writeln!(out, "Loaded")?;
+            print_metadata(&mut out, Some(&puzzle))?;
+            let deadline = ctx.deadline();
+            let start = Instant::now();
+            match ctx.engine.solve_until(&mut board, deadline) {
+                Ok(true) => {
+                    writeln!(out, "Solved board in {:?}", start.elapsed())?;
+                    write!(out, "{}", ctx.draw(&board))?;
+                    match ctx.rate(&board, deadline) {
+                        Ok(Some(difficulty)) => writeln!(out, "Difficulty: {}", difficulty)?,
+                        Ok(None) => {}
+                        Err(e) => writeln!(out, "{}", Failure::from(e))?,
+                    }
+                }
+                Ok(false) => writeln!(out, "No solution found after {:?}", start.elapsed())?,
+                Err(e) => writeln!(out, "{}", Failure::from(e))?,
+            }
+        } else {
+            writeln!(out, "{} is not a valid id", line)?;
+        }
+    }
+}
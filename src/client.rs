@@ -0,0 +1,531 @@
+use isahc::config::Configurable;
+use isahc::http::{StatusCode, Uri};
+use isahc::{AsyncReadResponseExt, HttpClient, ReadResponseExt};
+use serde::de::DeserializeOwned;
+use std::env;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Where puzzles are served from
+pub const DEFAULT_BASE_URL: &str = "https://www.andrewt.net/puzzles/cell-tower/puzzles";
+
+/// Settings for talking to the puzzle site
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Url that puzzle ids are appended to
+    pub base_url: String,
+    /// Maximum time to wait for a connection to be established
+    pub connect_timeout: Duration,
+    /// Maximum time to wait without receiving any data
+    pub read_timeout: Duration,
+    /// Number of extra attempts made after a failed request
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on every following attempt
+    pub backoff: Duration,
+    /// Upper bound on the delay between retries
+    pub max_backoff: Duration,
+    /// Maximum number of requests started per second, 0 for unlimited
+    pub requests_per_second: f64,
+    /// Value sent in the User-Agent header
+    pub user_agent: String,
+    /// Proxy to send `http://` requests through
+    pub http_proxy: Option<Uri>,
+    /// Proxy to send `https://` requests through
+    pub https_proxy: Option<Uri>,
+    /// Hosts which should never be proxied
+    pub no_proxy: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            max_retries: 4,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            requests_per_second: 2.0,
+            user_agent: format!(
+                "{}/{} (+https://github.com/fennewald/cell-tower)",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            ),
+            http_proxy: None,
+            https_proxy: None,
+            no_proxy: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Default settings, with the proxy taken from the environment
+    ///
+    /// Looks at `HTTPS_PROXY` for https urls and `HTTP_PROXY` for http urls
+    /// (either case), falling back to `ALL_PROXY` for both, and `NO_PROXY`
+    /// for hosts to exclude.
+    pub fn from_env() -> Config {
+        let proxy = |name: &str| {
+            [name, "all_proxy"]
+                .iter()
+                .filter_map(|name| env_var(name))
+                .find_map(|value| value.parse().ok())
+        };
+        let no_proxy = env_var("no_proxy")
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|host| !host.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Config {
+            http_proxy: proxy("http_proxy"),
+            https_proxy: proxy("https_proxy"),
+            no_proxy,
+            ..Config::default()
+        }
+    }
+
+    /// The proxy to use for a url, chosen by its scheme
+    pub fn proxy_for(&self, url: &str) -> Option<&Uri> {
+        let scheme = url.split_once("://").map_or("", |(scheme, _)| scheme);
+        if scheme.eq_ignore_ascii_case("https") {
+            self.https_proxy.as_ref()
+        } else if scheme.eq_ignore_ascii_case("http") {
+            self.http_proxy.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Delay before the given retry attempt, starting at 0
+    pub fn backoff_for(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        self.backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+
+    /// Minimum gap between the start of two requests
+    fn min_interval(&self) -> Option<Duration> {
+        if self.requests_per_second > 0.0 {
+            Some(Duration::from_secs_f64(1.0 / self.requests_per_second))
+        } else {
+            None
+        }
+    }
+}
+
+/// Read an environment variable, trying the lowercase then uppercase name
+fn env_var(name: &str) -> Option<String> {
+    env::var(name)
+        .or_else(|_| env::var(name.to_uppercase()))
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be built or sent
+    Http(isahc::Error),
+    /// The server answered with a non-success status
+    Status(StatusCode),
+    /// The body could not be decoded
+    Json(serde_json::Error),
+    /// The body could not be read
+    Io(std::io::Error),
+}
+
+impl Error {
+    /// Test if the request is worth trying again
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Http(e) => e.is_network() || e.is_timeout() || e.is_server(),
+            Error::Status(status) => status.is_server_error(),
+            Error::Io(_) => true,
+            Error::Json(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "request failed: {}", e),
+            Error::Status(status) => write!(f, "server returned {}", status),
+            Error::Json(e) => write!(f, "invalid response body: {}", e),
+            Error::Io(e) => write!(f, "couldn't read response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<isahc::Error> for Error {
+    fn from(e: isahc::Error) -> Error {
+        Error::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Json(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+/// Spaces out requests so that no more than a set number start per second
+pub struct RateLimiter {
+    interval: Option<Duration>,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Option<Duration>) -> RateLimiter {
+        RateLimiter {
+            interval,
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Reserve the next free slot, returning how long to wait until it
+    pub fn reserve(&self) -> Duration {
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => return Duration::ZERO,
+        };
+        let now = Instant::now();
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let slot = next_slot.map_or(now, |slot| slot.max(now));
+        *next_slot = Some(slot + interval);
+        slot - now
+    }
+
+    /// Block until the next request may be started
+    pub fn wait(&self) {
+        let delay = self.reserve();
+        if !delay.is_zero() {
+            thread::sleep(delay);
+        }
+    }
+}
+
+/// A polite http client for the puzzle site
+pub struct Client {
+    config: Config,
+    http: HttpClient,
+    limiter: RateLimiter,
+}
+
+impl Client {
+    /// Build a client with the given settings
+    pub fn new(config: Config) -> Result<Client, Error> {
+        let http = HttpClient::builder()
+            .connect_timeout(config.connect_timeout)
+            .low_speed_timeout(1, config.read_timeout)
+            .proxy(config.proxy_for(&config.base_url).cloned())
+            .proxy_blacklist(config.no_proxy.clone())
+            .default_header("user-agent", config.user_agent.as_str())
+            .build()?;
+        let limiter = RateLimiter::new(config.min_interval());
+        Ok(Client {
+            config,
+            http,
+            limiter,
+        })
+    }
+
+    /// Build a client with default settings and the proxy from the environment
+    pub fn from_env() -> Result<Client, Error> {
+        Client::new(Config::from_env())
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The underlying isahc client
+    pub fn http(&self) -> &HttpClient {
+        &self.http
+    }
+
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// Url of the puzzle with the given id
+    pub fn puzzle_url(&self, id: usize) -> String {
+        format!("{}/{}.json", self.config.base_url.trim_end_matches('/'), id)
+    }
+
+    /// Fetch and decode a json document, retrying transient failures
+    pub fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            self.limiter.wait();
+            match self.get_json_once(url) {
+                Err(e) if e.is_transient() && attempt < self.config.max_retries => {
+                    thread::sleep(self.config.backoff_for(attempt));
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    fn get_json_once<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        let mut response = self.http.get(url)?;
+        if !response.status().is_success() {
+            return Err(Error::Status(response.status()));
+        }
+        let body = response.bytes()?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Fetch and decode a json document without blocking the caller
+    ///
+    /// Follows the same retry and rate limiting rules as `get_json`.
+    pub async fn get_json_async<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            Delay::new(self.limiter.reserve()).await;
+            match self.get_json_once_async(url).await {
+                Err(e) if e.is_transient() && attempt < self.config.max_retries => {
+                    Delay::new(self.config.backoff_for(attempt)).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    async fn get_json_once_async<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        let mut response = self.http.get_async(url).await?;
+        if !response.status().is_success() {
+            return Err(Error::Status(response.status()));
+        }
+        let body = response.bytes().await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+/// A future which completes once the given duration has passed
+///
+/// The wait happens on a helper thread, so this works under any executor.
+pub struct Delay {
+    duration: Duration,
+    state: Option<Arc<Mutex<DelayState>>>,
+}
+
+struct DelayState {
+    done: bool,
+    waker: Waker,
+}
+
+impl Delay {
+    pub fn new(duration: Duration) -> Delay {
+        Delay {
+            duration,
+            state: None,
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.duration.is_zero() {
+            return Poll::Ready(());
+        }
+        match &self.state {
+            Some(state) => {
+                let mut state = state.lock().unwrap();
+                if state.done {
+                    Poll::Ready(())
+                } else {
+                    state.waker = cx.waker().clone();
+                    Poll::Pending
+                }
+            }
+            None => {
+                let state = Arc::new(Mutex::new(DelayState {
+                    done: false,
+                    waker: cx.waker().clone(),
+                }));
+                let shared = state.clone();
+                let duration = self.duration;
+                thread::spawn(move || {
+                    thread::sleep(duration);
+                    let mut state = shared.lock().unwrap();
+                    state.done = true;
+                    state.waker.wake_by_ref();
+                });
+                self.state = Some(state);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A canned reply from the mock server
+    pub(crate) struct Reply {
+        pub status: u16,
+        pub body: &'static str,
+        pub delay: Duration,
+    }
+
+    pub(crate) fn reply(status: u16, body: &'static str) -> Reply {
+        Reply {
+            status,
+            body,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Serve the given replies in order on a local port, one per connection
+    ///
+    /// Returns the base url and a counter of requests received
+    pub(crate) fn mock_server(replies: Vec<Reply>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        thread::spawn(move || {
+            for reply in replies {
+                let (mut stream, _) = match listener.accept() {
+                    Ok(conn) => conn,
+                    Err(_) => return,
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok() && line != "\r\n" && !line.is_empty() {
+                    line.clear();
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                thread::sleep(reply.delay);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    reply.status,
+                    reply.body.len(),
+                    reply.body
+                );
+            }
+        });
+        (url, hits)
+    }
+
+    pub(crate) fn test_config(base_url: String) -> Config {
+        Config {
+            base_url,
+            connect_timeout: Duration::from_secs(2),
+            read_timeout: Duration::from_secs(2),
+            max_retries: 2,
+            backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+            requests_per_second: 0.0,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        let (url, hits) = mock_server(vec![reply(503, ""), reply(500, ""), reply(200, "[1, 2]")]);
+        let client = Client::new(test_config(url)).unwrap();
+        let res: Vec<u32> = client.get_json(&client.puzzle_url(1)).unwrap();
+        assert_eq!(res, vec![1, 2]);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn gives_up_after_max_retries() {
+        let (url, hits) = mock_server(vec![reply(502, ""), reply(502, ""), reply(502, "")]);
+        let client = Client::new(test_config(url)).unwrap();
+        let res: Result<Vec<u32>, _> = client.get_json(&client.puzzle_url(1));
+        assert!(matches!(res, Err(Error::Status(s)) if s.as_u16() == 502));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        let (url, hits) = mock_server(vec![reply(404, ""), reply(200, "[]")]);
+        let client = Client::new(test_config(url)).unwrap();
+        let res: Result<Vec<u32>, _> = client.get_json(&client.puzzle_url(1));
+        assert!(matches!(res, Err(Error::Status(s)) if s.as_u16() == 404));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn read_timeout() {
+        let mut slow = reply(200, "[]");
+        slow.delay = Duration::from_secs(3);
+        let (url, _) = mock_server(vec![slow]);
+        let mut config = test_config(url);
+        config.read_timeout = Duration::from_secs(1);
+        config.max_retries = 0;
+        let client = Client::new(config).unwrap();
+        let res: Result<Vec<u32>, _> = client.get_json(&client.puzzle_url(1));
+        assert!(matches!(res, Err(ref e) if e.is_transient()));
+    }
+
+    #[test]
+    fn async_retries_server_errors() {
+        let (url, hits) = mock_server(vec![reply(500, ""), reply(200, "[3]")]);
+        let client = Client::new(test_config(url)).unwrap();
+        let res: Vec<u32> =
+            futures_lite::future::block_on(client.get_json_async(&client.puzzle_url(1))).unwrap();
+        assert_eq!(res, vec![3]);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn rate_limit_spaces_requests() {
+        let limiter = RateLimiter::new(Some(Duration::from_millis(100)));
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+        assert!(limiter.reserve() > Duration::from_millis(90));
+        assert!(limiter.reserve() > Duration::from_millis(190));
+    }
+
+    #[test]
+    fn proxy_follows_the_scheme() {
+        let config = Config {
+            http_proxy: Some("http://plain:3128".parse().unwrap()),
+            https_proxy: Some("http://secure:3128".parse().unwrap()),
+            ..Config::default()
+        };
+        let host = |url: &str| config.proxy_for(url).and_then(|p| p.host()).map(String::from);
+        assert_eq!(host("https://example.com/1.json").as_deref(), Some("secure"));
+        assert_eq!(host("HTTP://example.com/1.json").as_deref(), Some("plain"));
+        assert_eq!(host("example.com"), None);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let config = test_config(String::new());
+        assert_eq!(config.backoff_for(0), Duration::from_millis(10));
+        assert_eq!(config.backoff_for(2), Duration::from_millis(40));
+        assert_eq!(config.backoff_for(3), Duration::from_millis(50));
+        assert_eq!(config.backoff_for(40), Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,432 @@
+use crate::dlx;
+use crate::{Board, Point, PointSet};
+use std::fmt;
+use std::io::{self, Write};
+
+/// A board's search as a satisfiability problem, in conjunctive normal form
+///
+/// There is one variable per way of placing a word, numbered from 1 in the
+/// order `Board::all_words` lists them, and clauses saying each free cell is
+/// covered by exactly one placed word. As with dancing links, every placement
+/// is traced forwards in reading order from its first cell, so the reading
+/// order rule holds for any exact cover without clauses of its own.
+pub struct Cnf {
+    /// The placement behind each variable, variable `i + 1` at index `i`
+    pub placements: Vec<PointSet>,
+    /// Each clause lists literals, a variable's number or its negation
+    pub clauses: Vec<Vec<i32>>,
+}
+
+/// Why an assignment couldn't be read or isn't a solution
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CnfError {
+    /// The text isn't an assignment, at the given line counting from 1
+    Parse { line: usize, message: String },
+    /// A variable outside the problem was given a value
+    NoSuchVariable { variable: u32, variables: usize },
+    /// The solver reported that there's no solution
+    Unsatisfiable,
+    /// The assignment leaves a clause false, counting from 1
+    Unsatisfied { clause: usize },
+    /// The chosen words don't make a legal solution, failing at this word
+    NotASolution { word: usize },
+    /// The words are a solution, but not one the constraints allow
+    Constrained,
+}
+
+impl fmt::Display for CnfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CnfError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            CnfError::NoSuchVariable {
+                variable,
+                variables,
+            } => write!(
+                f,
+                "variable {} is out of range, the puzzle has {} variables",
+                variable, variables
+            ),
+            CnfError::Unsatisfiable => write!(f, "the assignment says there's no solution"),
+            CnfError::Unsatisfied { clause } => {
+                write!(f, "clause {} isn't satisfied", clause)
+            }
+            CnfError::NotASolution { word } => {
+                write!(
+                    f,
+                    "the chosen words aren't a legal solution at word {}",
+                    word + 1
+                )
+            }
+            CnfError::Constrained => write!(f, "the solution breaks a constraint"),
+        }
+    }
+}
+
+impl std::error::Error for CnfError {}
+
+impl Cnf {
+    /// Build the problem for the free cells of a board
+    pub fn of(board: &Board) -> Cnf {
+        let placements = board.all_words();
+        let mut covering: Vec<Vec<i32>> = vec![Vec::new(); 84];
+        for (i, placement) in placements.iter().enumerate() {
+            for p in placement.points() {
+                covering[cell(*p)].push(i as i32 + 1);
+            }
+        }
+        let mut clauses = Vec::new();
+        for y in 0..12 {
+            for x in 0..7 {
+                let p = Point::new(x, y);
+                if board.word_id(p).is_some() {
+                    continue;
+                }
+                // At least one word covers the cell...
+                let vars = &covering[cell(p)];
+                clauses.push(vars.clone());
+                // ...and no two do
+                for (i, a) in vars.iter().enumerate() {
+                    for b in &vars[i + 1..] {
+                        clauses.push(vec![-a, -b]);
+                    }
+                }
+            }
+        }
+        Cnf {
+            placements,
+            clauses,
+        }
+    }
+
+    pub fn variables(&self) -> usize {
+        self.placements.len()
+    }
+
+    /// Write the problem in DIMACS format, with a comment naming the word
+    /// and cells behind each variable
+    pub fn write(&self, board: &Board, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "c cell tower puzzle, one variable per placed word")?;
+        for (i, placement) in self.placements.iter().enumerate() {
+            writeln!(
+                out,
+                "c {} {} {}",
+                i + 1,
+                board.spell(placement).to_uppercase(),
+                placement
+            )?;
+        }
+        writeln!(out, "p cnf {} {}", self.variables(), self.clauses.len())?;
+        for clause in &self.clauses {
+            for literal in clause {
+                write!(out, "{} ", literal)?;
+            }
+            writeln!(out, "0")?;
+        }
+        Ok(())
+    }
+
+    /// Read a solver's output: `v` lines or bare literals, ending in 0
+    ///
+    /// Comment and `s` status lines are skipped, as are `SAT` and `UNSAT`
+    /// lines as written by minisat. Variables left out are false.
+    pub fn parse_assignment(&self, text: &str) -> Result<Vec<bool>, CnfError> {
+        let mut values = vec![false; self.variables()];
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let line = match line.split_whitespace().next() {
+                None | Some("c") => continue,
+                Some("s") | Some("SAT") | Some("UNSAT") | Some("UNSATISFIABLE") => {
+                    if line.contains("UNSAT") {
+                        return Err(CnfError::Unsatisfiable);
+                    }
+                    continue;
+                }
+                Some("v") => &line[1..],
+                Some(_) => line,
+            };
+            for word in line.split_whitespace() {
+                let literal: i32 = word.parse().map_err(|_| CnfError::Parse {
+                    line: n + 1,
+                    message: format!("expected a literal, found {:?}", word),
+                })?;
+                if literal == 0 {
+                    return Ok(values);
+                }
+                match values.get_mut(literal.unsigned_abs() as usize - 1) {
+                    Some(value) => *value = literal > 0,
+                    None => {
+                        return Err(CnfError::NoSuchVariable {
+                            variable: literal.unsigned_abs(),
+                            variables: self.variables(),
+                        })
+                    }
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// Check an assignment satisfies every clause and turn it into a
+    /// solved board, checking the words are a legal solution
+    pub fn solution(&self, board: &Board, values: &[bool]) -> Result<Board, CnfError> {
+        let value = |literal: i32| values[literal.unsigned_abs() as usize - 1] == (literal > 0);
+        if let Some(i) = self
+            .clauses
+            .iter()
+            .position(|c| !c.iter().any(|&l| value(l)))
+        {
+            return Err(CnfError::Unsatisfied { clause: i + 1 });
+        }
+        let mut words: Vec<&PointSet> = self
+            .placements
+            .iter()
+            .zip(values)
+            .filter(|(_, &v)| v)
+            .map(|(p, _)| p)
+            .collect();
+        words.sort_by_key(|w| (w.points()[0].y, w.points()[0].x));
+        let in_order: Vec<PointSet> = words.iter().map(|&w| w.clone()).collect();
+        board
+            .check_words(&in_order)
+            .map_err(|word| CnfError::NotASolution { word })?;
+        let solved = dlx::place_cover(board, &words);
+        if !solved.is_solution() {
+            return Err(CnfError::Constrained);
+        }
+        Ok(solved)
+    }
+
+    /// Find a satisfying assignment with a small DPLL solver, or `None` if
+    /// there isn't one
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let mut dpll = Dpll::new(self);
+        if !dpll.start() || !dpll.search() {
+            return None;
+        }
+        Some(dpll.values.iter().map(|v| v == &Some(true)).collect())
+    }
+}
+
+/// Index of a cell in a list of every cell
+fn cell(p: Point) -> usize {
+    p.y as usize * 7 + p.x as usize
+}
+
+/// Position of a literal in a list of both literals of every variable
+fn literal_index(literal: i32) -> usize {
+    (literal.unsigned_abs() as usize - 1) * 2 + (literal < 0) as usize
+}
+
+/// Backtracking search with unit propagation
+struct Dpll<'a> {
+    clauses: &'a [Vec<i32>],
+    /// Clauses each literal appears in, indexed by `literal_index`
+    occurs: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    /// Variables in the order they were given values
+    trail: Vec<usize>,
+}
+
+impl<'a> Dpll<'a> {
+    fn new(cnf: &'a Cnf) -> Dpll<'a> {
+        let mut occurs = vec![Vec::new(); cnf.variables() * 2];
+        for (i, clause) in cnf.clauses.iter().enumerate() {
+            for &literal in clause {
+                occurs[literal_index(literal)].push(i);
+            }
+        }
+        Dpll {
+            clauses: &cnf.clauses,
+            occurs,
+            values: vec![None; cnf.variables()],
+            trail: Vec::new(),
+        }
+    }
+
+    fn value(&self, literal: i32) -> Option<bool> {
+        self.values[literal.unsigned_abs() as usize - 1].map(|v| v == (literal > 0))
+    }
+
+    fn assign(&mut self, literal: i32) {
+        let var = literal.unsigned_abs() as usize - 1;
+        self.values[var] = Some(literal > 0);
+        self.trail.push(var);
+    }
+
+    fn undo(&mut self, mark: usize) {
+        for var in self.trail.drain(mark..) {
+            self.values[var] = None;
+        }
+    }
+
+    /// Give values to the unit clauses, failing on an empty or false one
+    fn start(&mut self) -> bool {
+        for clause in self.clauses {
+            match clause[..] {
+                [] => return false,
+                [literal] => match self.value(literal) {
+                    Some(false) => return false,
+                    Some(true) => {}
+                    None => self.assign(literal),
+                },
+                _ => {}
+            }
+        }
+        self.propagate(0)
+    }
+
+    /// Follow every clause left with one free literal, from the given point
+    /// in the trail, returning false if some clause became false
+    fn propagate(&mut self, mut next: usize) -> bool {
+        while next < self.trail.len() {
+            let var = self.trail[next] as i32 + 1;
+            next += 1;
+            let falsified = if self.values[var as usize - 1] == Some(true) {
+                -var
+            } else {
+                var
+            };
+            let index = literal_index(falsified);
+            for i in 0..self.occurs[index].len() {
+                let clause = &self.clauses[self.occurs[index][i]];
+                let mut free = None;
+                let mut n_free = 0;
+                let mut satisfied = false;
+                for &literal in clause {
+                    match self.value(literal) {
+                        Some(true) => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(false) => {}
+                        None => {
+                            n_free += 1;
+                            free = Some(literal);
+                        }
+                    }
+                }
+                match (satisfied, n_free) {
+                    (true, _) => {}
+                    (false, 0) => return false,
+                    (false, 1) => self.assign(free.unwrap()),
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// Branch on a free literal of the unsatisfied clause with the fewest
+    /// free literals, which for this problem is the least covered cell
+    fn choose(&self) -> Option<i32> {
+        let mut best: Option<(usize, i32)> = None;
+        for clause in self.clauses {
+            if clause.iter().any(|&l| self.value(l) == Some(true)) {
+                continue;
+            }
+            let mut free = clause.iter().filter(|&&l| self.value(l).is_none());
+            // Propagation leaves no clause false, so there's always one
+            let Some(&first) = free.next() else {
+                continue;
+            };
+            let n_free = free.count() + 1;
+            if best.is_none_or(|(n, _)| n_free < n) {
+                best = Some((n_free, first));
+            }
+        }
+        best.map(|(_, literal)| literal)
+    }
+
+    fn search(&mut self) -> bool {
+        let Some(literal) = self.choose() else {
+            return true;
+        };
+        for literal in [literal, -literal] {
+            let mark = self.trail.len();
+            self.assign(literal);
+            if self.propagate(mark) && self.search() {
+                return true;
+            }
+            self.undo(mark);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+
+    #[test]
+    fn round_trips_through_dimacs() {
+        let board = Board::try_from(GRID).unwrap();
+        let cnf = Cnf::of(&board);
+        let mut text = Vec::new();
+        cnf.write(&board, &mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.contains(&format!(
+            "p cnf {} {}\n",
+            cnf.variables(),
+            cnf.clauses.len()
+        )));
+
+        let values = cnf.solve().unwrap();
+        let solved = cnf.solution(&board, &values).unwrap();
+        assert!(solved.is_done());
+        let mut other = board.clone();
+        assert!(other
+            .enumerate_solutions()
+            .iter()
+            .any(|s| s.same_solution(&solved)));
+
+        // As a solver would print it
+        let mut output = "s SATISFIABLE\nv".to_string();
+        for (i, &v) in values.iter().enumerate() {
+            let var = i as i32 + 1;
+            output += &format!(" {}", if v { var } else { -var });
+        }
+        output += " 0\n";
+        assert_eq!(cnf.parse_assignment(&output).unwrap(), values);
+        assert_eq!(
+            cnf.parse_assignment("s UNSATISFIABLE\n"),
+            Err(CnfError::Unsatisfiable)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_assignments() {
+        let board = Board::try_from(GRID).unwrap();
+        let cnf = Cnf::of(&board);
+        let nothing = vec![false; cnf.variables()];
+        assert!(matches!(
+            cnf.solution(&board, &nothing),
+            Err(CnfError::Unsatisfied { .. })
+        ));
+        assert!(matches!(
+            cnf.parse_assignment("1 x 0"),
+            Err(CnfError::Parse { line: 1, .. })
+        ));
+        let too_big = format!("{} 0", cnf.variables() + 1);
+        assert!(matches!(
+            cnf.parse_assignment(&too_big),
+            Err(CnfError::NoSuchVariable { .. })
+        ));
+        assert_eq!(
+            cnf.parse_assignment("-2147483648 0"),
+            Err(CnfError::NoSuchVariable {
+                variable: 2147483648,
+                variables: cnf.variables(),
+            })
+        );
+    }
+
+    #[test]
+    fn finds_unsatisfiable_problems() {
+        let cnf = Cnf {
+            placements: vec![PointSet::empty(), PointSet::empty()],
+            clauses: vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]],
+        };
+        assert_eq!(cnf.solve(), None);
+    }
+}
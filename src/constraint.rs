@@ -0,0 +1,189 @@
+use crate::{Board, PointSet};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use std::fmt;
+use std::sync::Arc;
+
+/// An extra rule a solution must follow, on top of the puzzle's own
+///
+/// `allows_word` is asked about every candidate word as it is found, so
+/// rules about single words prune the search early. `allows_solution` is
+/// asked about each full board before it counts as a solution, for rules
+/// which can only be judged once every word is in place.
+pub trait Constraint: fmt::Display + Send + Sync {
+    /// Test if a word covering `points` may be placed next on `board`
+    fn allows_word(&self, _board: &Board, _points: &PointSet) -> bool {
+        true
+    }
+
+    /// Test if a full board is an acceptable solution
+    fn allows_solution(&self, _board: &Board) -> bool {
+        true
+    }
+}
+
+/// The given word must be part of every solution
+pub struct Required(pub String);
+
+impl Constraint for Required {
+    fn allows_solution(&self, board: &Board) -> bool {
+        board.words().iter().any(|w| w.word == self.0)
+    }
+}
+
+impl fmt::Display for Required {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} must be used", self.0.to_uppercase())
+    }
+}
+
+/// The given word may not be used
+pub struct Banned(pub String);
+
+impl Constraint for Banned {
+    fn allows_word(&self, board: &Board, points: &PointSet) -> bool {
+        points.length() != self.0.len() || board.spell(points) != self.0
+    }
+}
+
+impl fmt::Display for Banned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} may not be used", self.0.to_uppercase())
+    }
+}
+
+/// A side of the board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Edge::Left => "left",
+            Edge::Right => "right",
+            Edge::Top => "top",
+            Edge::Bottom => "bottom",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A limit on the shape of every word
+pub enum Shape {
+    /// No word may cover more than this many rows
+    MaxRows(usize),
+    /// Every word must cover a cell on this edge
+    TouchEdge(Edge),
+}
+
+impl Constraint for Shape {
+    fn allows_word(&self, _board: &Board, points: &PointSet) -> bool {
+        let points = points.points();
+        match self {
+            Shape::MaxRows(n) => {
+                let top = points.iter().map(|p| p.y).min().unwrap_or(0);
+                let bottom = points.iter().map(|p| p.y).max().unwrap_or(0);
+                ((bottom - top) as usize) < *n
+            }
+            Shape::TouchEdge(edge) => points.iter().any(|p| match edge {
+                Edge::Left => p.x == 0,
+                Edge::Right => p.x == 6,
+                Edge::Top => p.y == 0,
+                Edge::Bottom => p.y == 11,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shape::MaxRows(n) => write!(f, "no word may cover more than {} rows", n),
+            Shape::TouchEdge(edge) => write!(f, "every word must touch the {} edge", edge),
+        }
+    }
+}
+
+/// The constraints a board is searched under, cheap to clone along with it
+#[derive(Clone, Default)]
+pub struct Constraints(Arc<Vec<Box<dyn Constraint>>>);
+
+impl Constraints {
+    pub fn new(constraints: Vec<Box<dyn Constraint>>) -> Constraints {
+        Constraints(Arc::new(constraints))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Constraint> {
+        self.0.iter().map(|c| c.as_ref())
+    }
+
+    pub fn allow_word(&self, board: &Board, points: &PointSet) -> bool {
+        self.0.iter().all(|c| c.allows_word(board, points))
+    }
+
+    pub fn allow_solution(&self, board: &Board) -> bool {
+        self.0.iter().all(|c| c.allows_solution(board))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+    use crate::Point;
+
+    fn solutions(constraints: Vec<Box<dyn Constraint>>) -> Vec<Board> {
+        let mut board = Board::try_from(GRID).unwrap();
+        board.set_constraints(Constraints::new(constraints));
+        board.enumerate_solutions()
+    }
+
+    #[test]
+    fn words_can_be_required_and_banned() {
+        let all = solutions(Vec::new());
+        let using = |word: &str| {
+            all.iter()
+                .filter(|b| b.words().iter().any(|w| w.word == word))
+                .count()
+        };
+        let required = solutions(vec![Box::new(Required("spinners".to_string()))]);
+        assert_eq!(required.len(), using("spinners"));
+        assert!(!required.is_empty());
+        let banned = solutions(vec![Box::new(Banned("spinners".to_string()))]);
+        assert_eq!(banned.len(), all.len() - using("spinners"));
+
+        // Banned words aren't offered at all
+        let mut board = Board::try_from(GRID).unwrap();
+        board.set_constraints(Constraints::new(vec![Box::new(Banned("flat".to_string()))]));
+        assert!(board.next_words().iter().all(|w| board.spell(w) != "flat"));
+    }
+
+    #[test]
+    fn shapes_are_limited() {
+        for solution in solutions(vec![Box::new(Shape::MaxRows(1))]) {
+            for word in solution.words() {
+                assert!(word
+                    .points
+                    .points()
+                    .iter()
+                    .all(|p| p.y == word.points.points()[0].y));
+            }
+        }
+        let shape = Shape::TouchEdge(Edge::Left);
+        let board = Board::try_from(GRID).unwrap();
+        let mut points = PointSet::new(Point::new(1, 0));
+        points.push(Point::new(2, 0));
+        assert!(!shape.allows_word(&board, &points));
+        assert_eq!(shape.to_string(), "every word must touch the left edge");
+    }
+}
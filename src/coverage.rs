@@ -0,0 +1,144 @@
+use crate::render::{self, Style};
+use crate::{Board, Point, PointSet};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+/// The words which could still cover a single free cell
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellCoverage {
+    pub cell: Point,
+    pub letter: char,
+    /// Number of placements, counting a word once for each set of cells it
+    /// could take
+    pub count: usize,
+    /// The distinct words among those placements, in alphabetical order
+    pub words: Vec<String>,
+}
+
+/// How many legal words could cover each free cell of a board
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Coverage {
+    /// Free cells, in reading order
+    pub cells: Vec<CellCoverage>,
+}
+
+impl Coverage {
+    /// Find every word which could start at a free cell, and count how many
+    /// cover each free cell
+    pub fn of(board: &Board) -> Coverage {
+        let mut placements: Vec<Vec<PointSet>> = vec![Vec::new(); 84];
+        for word in board.all_words() {
+            for p in word.points() {
+                placements[p.y as usize * 7 + p.x as usize].push(word.clone());
+            }
+        }
+
+        let mut cells = Vec::new();
+        for (i, placed) in placements.iter().enumerate() {
+            let cell = Point::new((i % 7) as u8, (i / 7) as u8);
+            if board.word_id(cell).is_some() {
+                continue;
+            }
+            let mut words: Vec<String> = placed.iter().map(|w| board.spell(w)).collect();
+            words.sort();
+            words.dedup();
+            cells.push(CellCoverage {
+                cell,
+                letter: board[cell] as char,
+                count: placed.len(),
+                words,
+            });
+        }
+        Coverage { cells }
+    }
+
+    /// Look up a free cell
+    pub fn get(&self, cell: Point) -> Option<&CellCoverage> {
+        self.cells.iter().find(|c| c.cell == cell)
+    }
+
+    /// Free cells no word can cover, which make the board unsolvable
+    pub fn stranded(&self) -> impl Iterator<Item = &CellCoverage> {
+        self.cells.iter().filter(|c| c.count == 0)
+    }
+}
+
+/// Draw the board with each free cell showing how many placements could
+/// cover it, shaded from red for none through to blue for many
+///
+/// Cells already covered show their letter. Shading follows the `colored`
+/// crate's settings, so is dropped when colour is turned off.
+pub fn heatmap(board: &Board, coverage: &Coverage, style: Style) -> String {
+    let mut text = render::render_marked(board, style, &|point, text| match coverage.get(point) {
+        Some(cell) => {
+            let count = if cell.count > 999 {
+                "+++".to_string()
+            } else {
+                format!("{:>3}", cell.count)
+            };
+            match cell.count {
+                0 => count.white().on_red().to_string(),
+                1 => count.black().on_yellow().to_string(),
+                2..=4 => count.black().on_green().to_string(),
+                _ => count.white().on_blue().to_string(),
+            }
+        }
+        None => text.dimmed().to_string(),
+    });
+    text.push_str(&format!(
+        "{} none  {} one  {} a few  {} five or more\n",
+        "   ".on_red(),
+        "   ".on_yellow(),
+        "   ".on_green(),
+        "   ".on_blue()
+    ));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+
+    #[test]
+    fn counts_words_over_each_cell() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let coverage = Coverage::of(&board);
+        assert_eq!(coverage.cells.len(), 84);
+        let corner = coverage.get(Point::new(0, 0)).unwrap();
+        // Only words starting at the corner can cover it
+        assert_eq!(corner.count, board.next_words().len());
+        assert!(corner.words.contains(&"flat".to_string()));
+        assert_eq!(coverage.stranded().count(), 0);
+
+        // Placed cells drop out, and cutting off a cell strands it
+        let mut top = PointSet::empty();
+        for x in 0..6 {
+            top.push(Point::new(x, 0));
+        }
+        let mut second = PointSet::empty();
+        for x in 0..7 {
+            second.push(Point::new(x, 1));
+        }
+        board.insert_word(&top);
+        board.insert_word(&second);
+        let coverage = Coverage::of(&board);
+        assert_eq!(coverage.cells.len(), 84 - 13);
+        let stranded: Vec<Point> = coverage.stranded().map(|c| c.cell).collect();
+        assert_eq!(stranded, [Point::new(6, 0)]);
+    }
+
+    #[test]
+    fn heatmap_shows_counts() {
+        colored::control::set_override(false);
+        let board = Board::try_from(GRID).unwrap();
+        let coverage = Coverage::of(&board);
+        let text = heatmap(&board, &coverage, Style::Ascii);
+        let first = coverage.cells[0].count;
+        assert!(text
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with(&format!("|{:>3}", first)));
+    }
+}
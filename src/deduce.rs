@@ -0,0 +1,362 @@
+use crate::board::{PlacedWord, TimedOut};
+use crate::{Board, Point, PointSet};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Instant;
+
+/// Why a candidate word was ruled out
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "because", rename_all = "snake_case")]
+pub enum Because {
+    /// Placing the word would leave a cell no word could cover
+    Strands { cell: Point },
+    /// The word misses a cell which only the next word can reach
+    Misses { cell: Point },
+}
+
+/// One step of reasoning
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum Step {
+    /// A candidate for the next word was ruled out
+    Eliminate {
+        word: String,
+        points: PointSet,
+        #[serde(flatten)]
+        because: Because,
+    },
+    /// Only one candidate was left for the next word, so it was placed
+    OnlyCandidate {
+        root: Point,
+        word: String,
+        points: PointSet,
+    },
+    /// A cell only the next word can reach is covered by a single candidate,
+    /// so it was placed
+    OnlyCover {
+        cell: Point,
+        word: String,
+        points: PointSet,
+    },
+    /// Logic ran out with several candidates left, so the rest of the board
+    /// was found by search
+    Search {
+        candidates: Vec<String>,
+        words: Vec<PlacedWord>,
+    },
+    /// Every candidate was ruled out, so the board has no solution
+    Contradiction { root: Point },
+}
+
+fn upper(word: &str) -> String {
+    word.to_uppercase()
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Eliminate {
+                word,
+                because: Because::Strands { cell },
+                ..
+            } => write!(f, "placing {} would strand cell {}", upper(word), cell),
+            Step::Eliminate {
+                word,
+                because: Because::Misses { cell },
+                ..
+            } => write!(
+                f,
+                "{} can't be next, as it misses cell {} which no later word can reach",
+                upper(word),
+                cell
+            ),
+            Step::OnlyCandidate { root, word, .. } => write!(
+                f,
+                "the only candidate starting at {} is {}",
+                root,
+                upper(word)
+            ),
+            Step::OnlyCover { cell, word, .. } => {
+                write!(f, "cell {} can only be covered by {}", cell, upper(word))
+            }
+            Step::Search { candidates, words } => {
+                let candidates: Vec<String> = candidates.iter().map(|w| upper(w)).collect();
+                let words: Vec<String> = words.iter().map(|w| upper(&w.word)).collect();
+                if words.is_empty() {
+                    write!(
+                        f,
+                        "logic is stuck between {}, and search finds no solution",
+                        candidates.join(", ")
+                    )
+                } else {
+                    write!(
+                        f,
+                        "logic is stuck between {}, so search placed {}",
+                        candidates.join(", "),
+                        words.join(", ")
+                    )
+                }
+            }
+            Step::Contradiction { root } => {
+                write!(f, "no word starting at {} can be placed", root)
+            }
+        }
+    }
+}
+
+/// The reasoning used to solve a board
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Deduction {
+    pub steps: Vec<Step>,
+    pub solved: bool,
+    /// Words placed by logic before search was needed, or all of them
+    pub by_logic: usize,
+    /// The board was solved without any search
+    pub logic_only: bool,
+}
+
+/// Mark every free cell which some word could still cover, using only words
+/// starting at free cells beyond `after`, or anywhere if `after` is `None`
+///
+/// This ignores how the cells in between end up being covered, so it can
+/// only overestimate what is reachable.
+fn coverable(board: &Board, after: Option<Point>) -> [[bool; 7]; 12] {
+    let mut covered = [[false; 7]; 12];
+    for y in 0..12 {
+        for x in 0..7 {
+            let start = Point::new(x, y);
+            if after.is_some_and(|a| !start.is_beyond(&a)) {
+                continue;
+            }
+            for word in board.words_from(start) {
+                for p in word.points() {
+                    covered[p.y as usize][p.x as usize] = true;
+                }
+            }
+        }
+    }
+    covered
+}
+
+/// Free cells missing from a coverage map, in reading order
+fn uncovered(board: &Board, covered: &[[bool; 7]; 12]) -> Vec<Point> {
+    let mut cells = Vec::new();
+    for (y, row) in covered.iter().enumerate() {
+        for (x, &covered) in row.iter().enumerate() {
+            let p = Point::new(x as u8, y as u8);
+            if !covered && board.word_id(p).is_none() {
+                cells.push(p);
+            }
+        }
+    }
+    cells
+}
+
+/// Find the first cell left uncoverable by placing the given word
+fn strands(board: &Board, word: &PointSet) -> Option<Point> {
+    let mut next = board.clone();
+    next.insert_word(word);
+    uncovered(&next, &coverable(&next, None)).first().copied()
+}
+
+/// Rule out candidates for the next word which would strand a cell, or which
+/// miss a cell no later word can reach, recording each in `steps`
+///
+/// Returns the candidates left, and the cell which narrowed them down to
+/// one if that took the second rule.
+fn eliminate(
+    board: &Board,
+    steps: &mut Option<&mut Vec<Step>>,
+) -> (Vec<PointSet>, Option<Point>) {
+    let root = board.get_root().unwrap();
+    let mut candidates = board.next_words();
+
+    candidates.retain(|word| match strands(board, word) {
+        Some(cell) => {
+            if let Some(steps) = steps {
+                steps.push(Step::Eliminate {
+                    word: board.spell(word),
+                    points: word.clone(),
+                    because: Because::Strands { cell },
+                });
+            }
+            false
+        }
+        None => true,
+    });
+    if candidates.len() <= 1 {
+        return (candidates, None);
+    }
+
+    // Cells no later word can reach must be part of the next one
+    let later = coverable(board, Some(root));
+    for cell in uncovered(board, &later) {
+        if cell == root {
+            continue;
+        }
+        let (covering, missing): (Vec<PointSet>, Vec<PointSet>) = candidates
+            .into_iter()
+            .partition(|word| word.points().contains(&cell));
+        if let Some(steps) = steps {
+            for word in &missing {
+                steps.push(Step::Eliminate {
+                    word: board.spell(word),
+                    points: word.clone(),
+                    because: Because::Misses { cell },
+                });
+            }
+        }
+        candidates = covering;
+        if candidates.len() == 1 {
+            return (candidates, Some(cell));
+        }
+        if candidates.is_empty() {
+            break;
+        }
+    }
+    (candidates, None)
+}
+
+/// The candidates for the next word which the rules of `deduce` can't rule
+/// out, so a person would have to pick between them if there's more than one
+pub fn survivors(board: &Board) -> Vec<PointSet> {
+    if board.is_done() {
+        return Vec::new();
+    }
+    eliminate(board, &mut None).0
+}
+
+/// Solve a board step by step with rules a person could follow, explaining
+/// each one, and fall back to search when they run out
+///
+/// Words already on the board are kept.
+pub fn deduce(board: &mut Board, deadline: Option<Instant>) -> Result<Deduction, TimedOut> {
+    let mut steps = Vec::new();
+    while !board.is_done() {
+        if deadline.is_some_and(|d| Instant::now() > d) {
+            return Err(TimedOut);
+        }
+        let root = board.get_root().unwrap();
+        let (mut candidates, cell) = eliminate(board, &mut Some(&mut steps));
+
+        let mut placed = None;
+        if candidates.len() == 1 {
+            let word = candidates.pop().unwrap();
+            steps.push(match cell {
+                Some(cell) => Step::OnlyCover {
+                    cell,
+                    word: board.spell(&word),
+                    points: word.clone(),
+                },
+                None => Step::OnlyCandidate {
+                    root,
+                    word: board.spell(&word),
+                    points: word.clone(),
+                },
+            });
+            placed = Some(word);
+        }
+
+        match placed {
+            Some(word) => board.insert_word(&word),
+            None if candidates.is_empty() => {
+                steps.push(Step::Contradiction { root });
+                return Ok(finish(steps, false));
+            }
+            None => {
+                let before = board.n_words();
+                let solved = board.solve_until(deadline)?;
+                let words = board.words().into_iter().skip(before).collect();
+                steps.push(Step::Search {
+                    candidates: candidates.iter().map(|w| board.spell(w)).collect(),
+                    words,
+                });
+                return Ok(finish(steps, solved));
+            }
+        }
+    }
+    Ok(finish(steps, board.is_solution()))
+}
+
+fn finish(steps: Vec<Step>, solved: bool) -> Deduction {
+    let by_logic = steps
+        .iter()
+        .filter(|s| matches!(s, Step::OnlyCandidate { .. } | Step::OnlyCover { .. }))
+        .count();
+    let logic_only = solved && !steps.iter().any(|s| matches!(s, Step::Search { .. }));
+    Deduction {
+        steps,
+        solved,
+        by_logic,
+        logic_only,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+
+    fn row(y: u8, xs: std::ops::Range<u8>) -> PointSet {
+        let mut points = PointSet::empty();
+        for x in xs {
+            points.push(Point::new(x, y));
+        }
+        points
+    }
+
+    #[test]
+    fn explains_and_solves() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let deduction = deduce(&mut board, None).unwrap();
+        assert!(deduction.solved);
+        assert!(board.is_done());
+        // The grid has many solutions, so logic alone can't settle it
+        assert!(!deduction.logic_only);
+        assert!(matches!(deduction.steps.last(), Some(Step::Search { .. })));
+        // Every word placed by logic must be part of the final solution
+        for step in &deduction.steps {
+            if let Step::OnlyCandidate { points, .. } | Step::OnlyCover { points, .. } = step {
+                assert!(board.word_id(points.points()[0]).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn stranded_cells() {
+        let mut board = Board::try_from(GRID).unwrap();
+        board.insert_word(&row(0, 0..6));
+        board.insert_word(&row(1, 0..7));
+        // The top right cell can no longer be reached by anything
+        assert_eq!(
+            uncovered(&board, &coverable(&board, None)),
+            [Point::new(6, 0)]
+        );
+
+        let mut board = Board::try_from(GRID).unwrap();
+        assert_eq!(strands(&board, &row(0, 0..4)), None);
+        board.insert_word(&row(1, 0..7));
+        assert_eq!(strands(&board, &row(0, 0..6)), Some(Point::new(6, 0)));
+    }
+
+    #[test]
+    fn steps_read_well() {
+        let step = Step::OnlyCover {
+            cell: Point::new(3, 4),
+            word: "aardvark".to_string(),
+            points: row(4, 0..7),
+        };
+        assert_eq!(
+            step.to_string(),
+            "cell (3, 4) can only be covered by AARDVARK"
+        );
+        let step = Step::Eliminate {
+            word: "flat".to_string(),
+            points: row(0, 0..4),
+            because: Because::Strands {
+                cell: Point::new(6, 0),
+            },
+        };
+        assert_eq!(step.to_string(), "placing FLAT would strand cell (6, 0)");
+    }
+}
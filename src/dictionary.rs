@@ -1,3 +1,7 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
 
 pub struct Node {
     pub is_word: bool,
@@ -5,34 +9,41 @@ pub struct Node {
     children: [*const Node; 26],
 }
 
-// Nodes will only ever be made at compile time as static refs
+// Nodes are either made at compile time as static refs, or leaked when a
+// dictionary is loaded at runtime, and are never mutated once shared
 unsafe impl Send for Node {}
 unsafe impl Sync for Node {}
 
 impl Node {
     /// Load the node item with the given prefix
     fn get(&'static self, prefix: &[u8]) -> Option<&'static Node> {
-        if prefix.len() == 0 {
-            return Some(&self);
+        if prefix.is_empty() {
+            return Some(self);
         }
-        let index = (prefix[0] - b'a') as usize;
-        if let Some(child) = unsafe{ self.children[index].as_ref() } {
+        if let Some(child) = self.child(prefix[0]) {
             child.get(&prefix[1..])
         } else {
             None
         }
     }
 
-    /// Determine if the given character is valid at the current node
+    /// The child for the given letter, or `None` for any byte but a to z
+    fn child(&self, c: u8) -> Option<&'static Node> {
+        if !c.is_ascii_lowercase() {
+            return None;
+        }
+        unsafe { self.children[(c - b'a') as usize].as_ref() }
+    }
+
+    /// Determine if the given character is valid at the current node, which
+    /// it never is outside a to z
     pub fn valid_next(&self, c: u8) -> bool {
-        let index = (c - b'a') as usize;
-        unsafe { self.children[index].as_ref().is_some() }
+        self.child(c).is_some()
     }
 
-    /// Return the next node
+    /// Return the next node, `None` for any byte but a to z
     pub fn get_next(&'static self, c: u8) -> Option<&'static Node> {
-        let index = (c - b'a') as usize;
-        unsafe { self.children[index].as_ref() }
+        self.child(c)
     }
 
     pub fn is_word(&'static self, word: &[u8]) -> bool {
@@ -42,19 +53,217 @@ impl Node {
             false
         }
     }
+
+    /// Call `f` on every word below this node, in alphabetical order
+    fn for_each_word(&'static self, prefix: &mut Vec<u8>, f: &mut dyn FnMut(&[u8])) {
+        if self.is_word {
+            f(prefix);
+        }
+        for (i, child) in self.children.iter().enumerate() {
+            if let Some(child) = unsafe { child.as_ref() } {
+                prefix.push(b'a' + i as u8);
+                child.for_each_word(prefix, f);
+                prefix.pop();
+            }
+        }
+    }
 }
 
-pub fn first_node(c: u8) -> Option<&'static Node> {
-    let buffer = [c];
-    GEN__ROOT.get(&buffer)
+/// A trie being built at runtime, before it is frozen into `Node`s
+#[derive(Default)]
+struct Builder {
+    is_word: bool,
+    children: [Option<Box<Builder>>; 26],
+}
+
+impl Builder {
+    fn add(&mut self, word: &[u8]) {
+        match word.split_first() {
+            None => self.is_word = true,
+            Some((&c, rest)) => self.children[(c - b'a') as usize]
+                .get_or_insert_with(Default::default)
+                .add(rest),
+        }
+    }
+
+    /// Convert into a leaked, immutable, node
+    fn freeze(self) -> &'static Node {
+        let mut children = [std::ptr::null(); 26];
+        let mut n_children = self.is_word as u32;
+        for (i, child) in self.children.into_iter().enumerate() {
+            if let Some(child) = child {
+                let node = child.freeze();
+                n_children += node.n_children;
+                children[i] = node as *const Node;
+            }
+        }
+        Box::leak(Box::new(Node {
+            is_word: self.is_word,
+            n_children,
+            children,
+        }))
+    }
 }
 
-pub fn get_node(word: &[u8]) -> Option<&'static Node> {
-    GEN__ROOT.get(word)
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    /// A word contained something other than the letters a to z
+    InvalidWord { line: usize, word: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "couldn't read dictionary: {}", e),
+            LoadError::InvalidWord { line, word } => {
+                write!(f, "line {}: {:?} isn't made of the letters a-z", line, word)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+/// A set of words that boards may be tiled with
+#[derive(Clone, Copy)]
+pub struct Dictionary {
+    root: &'static Node,
+}
+
+impl Dictionary {
+    /// The word list compiled into the program
+    pub fn builtin() -> Dictionary {
+        Dictionary { root: &GEN__ROOT }
+    }
+
+    /// Build a dictionary from a list of words, one per line
+    ///
+    /// Words are lowercased, and blank lines and lines starting with `#` are
+    /// skipped. The dictionary lives for the rest of the program.
+    pub fn parse(text: &str) -> Result<Dictionary, LoadError> {
+        let mut builder = Builder::default();
+        for (i, line) in text.lines().enumerate() {
+            let word = line.trim().to_ascii_lowercase();
+            if word.is_empty() || word.starts_with('#') {
+                continue;
+            }
+            if !word.bytes().all(|c| c.is_ascii_lowercase()) {
+                return Err(LoadError::InvalidWord {
+                    line: i + 1,
+                    word: line.to_string(),
+                });
+            }
+            builder.add(word.as_bytes());
+        }
+        Ok(Dictionary {
+            root: builder.freeze(),
+        })
+    }
+
+    /// Load a word list from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Dictionary, LoadError> {
+        Dictionary::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The node reached after reading a single letter
+    ///
+    /// `None` for anything but a lowercase letter a to z.
+    pub fn first_node(&self, c: u8) -> Option<&'static Node> {
+        self.root.get(&[c])
+    }
+
+    /// The node reached after reading the given prefix
+    ///
+    /// Words are lowercase a to z only, so a prefix holding any other byte,
+    /// including uppercase letters, gives `None`.
+    pub fn get_node(&self, word: &[u8]) -> Option<&'static Node> {
+        self.root.get(word)
+    }
+
+    /// Test if the word is in the dictionary
+    ///
+    /// Any byte outside lowercase a to z, such as an uppercase letter,
+    /// punctuation or part of a multibyte character, makes this false.
+    pub fn is_word(&self, word: &[u8]) -> bool {
+        self.root.is_word(word)
+    }
+
+    /// Number of words in the dictionary
+    pub fn len(&self) -> usize {
+        self.root.n_children as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return every word, in alphabetical order
+    pub fn words(&self) -> Vec<String> {
+        let mut words = Vec::with_capacity(self.len());
+        self.root.for_each_word(&mut Vec::new(), &mut |word| {
+            words.push(String::from_utf8(word.to_vec()).unwrap())
+        });
+        words
+    }
 }
 
-pub fn is_word(word: &[u8]) -> bool {
-    GEN__ROOT.is_word(word)
+impl Default for Dictionary {
+    fn default() -> Dictionary {
+        Dictionary::builtin()
+    }
 }
 
 include!(concat!(env!("OUT_DIR"), "/wordlist.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_word_list() {
+        let dict = Dictionary::parse("# comment\nTower\ncell\n\ncells\n").unwrap();
+        assert_eq!(dict.len(), 3);
+        assert!(dict.is_word(b"tower"));
+        assert!(dict.is_word(b"cells"));
+        assert!(!dict.is_word(b"cel"));
+        assert_eq!(dict.words(), vec!["cell", "cells", "tower"]);
+    }
+
+    #[test]
+    fn reject_invalid_words() {
+        let res = Dictionary::parse("cell\nit's\n");
+        assert!(matches!(res, Err(LoadError::InvalidWord { line: 2, .. })));
+    }
+
+    #[test]
+    fn builtin_dictionary() {
+        let dict = Dictionary::builtin();
+        assert!(dict.is_word(b"aardvark"));
+        assert!(!dict.is_word(b"aardvar"));
+        assert_eq!(dict.len(), dict.words().len());
+    }
+
+    #[test]
+    fn punctuation_is_not_a_word() {
+        let dict = Dictionary::builtin();
+        assert!(!dict.is_word(b"it's"));
+        assert!(dict.get_node(b"'").is_none());
+        assert!(!dict.get_node(b"i").unwrap().valid_next(b'\''));
+    }
+
+    #[test]
+    fn accented_letters_are_not_words() {
+        let dict = Dictionary::builtin();
+        assert!(!dict.is_word("héllo".as_bytes()));
+        assert!(!dict.is_word("HELLO".as_bytes()));
+        assert!(dict.first_node(0xff).is_none());
+        assert!(dict.get_node(b"h").unwrap().get_next(0xc3).is_none());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::board::PlacedWord;
+use crate::render::{self, Style};
+use crate::{Board, Point};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A patch of the board which two solutions cut into words differently
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Difference {
+    /// Words of the first solution covering the patch, in reading order
+    pub left: Vec<PlacedWord>,
+    /// Words of the second solution covering the same cells
+    pub right: Vec<PlacedWord>,
+    /// Cells of the patch, in reading order
+    pub cells: Vec<Point>,
+}
+
+fn join(words: &[PlacedWord]) -> String {
+    let words: Vec<String> = words.iter().map(|w| w.word.to_uppercase()).collect();
+    words.join(" + ")
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} vs {}", join(&self.left), join(&self.right))
+    }
+}
+
+/// How two solutions to the same board differ
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comparison {
+    /// Words both solutions place on the same cells
+    pub shared: Vec<PlacedWord>,
+    pub differences: Vec<Difference>,
+}
+
+impl Comparison {
+    /// Pair up the words two solutions have in common, and group the rest
+    /// into the smallest patches which each solution covers exactly
+    pub fn of(left: &Board, right: &Board) -> Comparison {
+        let left_words = left.words();
+        let right_words = right.words();
+        let (shared, left_only): (Vec<PlacedWord>, Vec<PlacedWord>) = left_words
+            .into_iter()
+            .partition(|w| right_words.iter().any(|r| r.points == w.points));
+        let mut right_only: Vec<PlacedWord> = right_words
+            .into_iter()
+            .filter(|w| !shared.iter().any(|s| s.points == w.points))
+            .collect();
+
+        // Grow each patch from a word of the first solution, pulling in any
+        // word of either solution which overlaps it, until it stops growing
+        let overlaps = |a: &PlacedWord, b: &PlacedWord| {
+            a.points
+                .points()
+                .iter()
+                .any(|p| b.points.points().contains(p))
+        };
+        let mut left_only = left_only.into_iter();
+        let mut differences = Vec::new();
+        while let Some(first) = left_only.next() {
+            let mut patch_left = vec![first];
+            let mut patch_right: Vec<PlacedWord> = Vec::new();
+            let mut rest_left: Vec<PlacedWord> = left_only.collect();
+            loop {
+                let (joined, kept): (Vec<PlacedWord>, Vec<PlacedWord>) = right_only
+                    .into_iter()
+                    .partition(|r| patch_left.iter().any(|l| overlaps(l, r)));
+                right_only = kept;
+                let (more, kept): (Vec<PlacedWord>, Vec<PlacedWord>) = rest_left
+                    .into_iter()
+                    .partition(|l| joined.iter().any(|r| overlaps(l, r)));
+                rest_left = kept;
+                let grew = !more.is_empty();
+                patch_right.extend(joined);
+                patch_left.extend(more);
+                if !grew {
+                    break;
+                }
+            }
+            left_only = rest_left.into_iter();
+
+            patch_left.sort_by_key(|w| w.id);
+            patch_right.sort_by_key(|w| w.id);
+            let mut cells: Vec<Point> = patch_left
+                .iter()
+                .flat_map(|w| w.points.points().iter().copied())
+                .collect();
+            cells.sort_by_key(|p| (p.y, p.x));
+            differences.push(Difference {
+                left: patch_left,
+                right: patch_right,
+                cells,
+            });
+        }
+        differences.sort_by_key(|d| (d.cells[0].y, d.cells[0].x));
+        Comparison {
+            shared,
+            differences,
+        }
+    }
+
+    /// Test if a cell is covered differently by the two solutions
+    pub fn differs(&self, cell: Point) -> bool {
+        self.differences.iter().any(|d| d.cells.contains(&cell))
+    }
+}
+
+/// Draw two solutions side by side, showing letters only where they differ
+pub fn side_by_side(left: &Board, right: &Board, comparison: &Comparison, style: Style) -> String {
+    let mark = |point: Point, text: String| {
+        if comparison.differs(point) {
+            text.bold().to_string()
+        } else {
+            " · ".dimmed().to_string()
+        }
+    };
+    let left = render::render_marked(left, style, &mark);
+    let right = render::render_marked(right, style, &mark);
+    let mut out = String::new();
+    for (l, r) in left.lines().zip(right.lines()) {
+        out.push_str(l);
+        out.push_str("   ");
+        out.push_str(r);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+    use crate::PointSet;
+
+    #[test]
+    fn pairs_shared_words_and_groups_the_rest() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let solutions = board.enumerate_solutions();
+        assert!(solutions.len() > 1);
+        let (left, right) = (&solutions[0], &solutions[1]);
+        let comparison = Comparison::of(left, right);
+        assert!(!comparison.differences.is_empty());
+
+        // Every cell is either in a shared word or exactly one patch, and
+        // each patch is covered by both sides
+        let mut covered = 0;
+        for word in &comparison.shared {
+            covered += word.points.length();
+        }
+        for difference in &comparison.differences {
+            let count =
+                |words: &[PlacedWord]| words.iter().map(|w| w.points.length()).sum::<usize>();
+            assert_eq!(count(&difference.left), difference.cells.len());
+            assert_eq!(count(&difference.right), difference.cells.len());
+            assert_ne!(join(&difference.left), "");
+            covered += difference.cells.len();
+        }
+        assert_eq!(covered, 84);
+
+        assert!(Comparison::of(left, left).differences.is_empty());
+    }
+
+    #[test]
+    fn describes_a_patch() {
+        let word = |id, word: &str| PlacedWord {
+            id,
+            word: word.to_string(),
+            points: PointSet::new(Point::new(0, 0)),
+        };
+        let difference = Difference {
+            left: vec![word(0, "flower"), word(1, "teas")],
+            right: vec![word(0, "flow"), word(1, "erteas")],
+            cells: Vec::new(),
+        };
+        assert_eq!(difference.to_string(), "FLOWER + TEAS vs FLOW + ERTEAS");
+    }
+}
@@ -0,0 +1,243 @@
+use crate::board::TimedOut;
+use crate::deduce;
+use crate::frame::Frame;
+use crate::frequency::Frequencies;
+use crate::Board;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Instant;
+
+/// A rough label for a difficulty score
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Band {
+    Easy,
+    Medium,
+    Hard,
+    Fiendish,
+}
+
+impl Band {
+    pub fn of(score: f64) -> Band {
+        if score < 25.0 {
+            Band::Easy
+        } else if score < 50.0 {
+            Band::Medium
+        } else if score < 75.0 {
+            Band::Hard
+        } else {
+            Band::Fiendish
+        }
+    }
+}
+
+impl fmt::Display for Band {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Band::Easy => "easy",
+            Band::Medium => "medium",
+            Band::Hard => "hard",
+            Band::Fiendish => "fiendish",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How hard a puzzle is for a person, judged from how the solver got on
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Difficulty {
+    /// From 0 for trivial to 100 for the hardest
+    pub score: f64,
+    pub band: Band,
+    /// Number of candidates from `next_words` before each word of the solution
+    pub branching: Vec<usize>,
+    /// Number of those candidates the rules of `deduce` can't rule out
+    pub survivors: Vec<usize>,
+    /// Words which were the only candidate left by those rules
+    pub forced: usize,
+    /// Words picked from several candidates the rules couldn't tell apart
+    pub guesses: usize,
+    /// The most words the search placed down a wrong path before backing out
+    pub backtrack_depth: usize,
+    pub backtracks: u64,
+    /// Average rarity of the solution's words, from 0 for common to 1 for
+    /// rare, when there's a frequency list to judge it by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rarity: Option<f64>,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1} ({}): {} forced, {} guesses, backtracked up to {} words deep",
+            self.score, self.band, self.forced, self.guesses, self.backtrack_depth
+        )?;
+        match self.rarity {
+            Some(rarity) => write!(f, ", word rarity {:.2}", rarity),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Finds how many words deep a search went down a wrong path before backing
+/// out, following its trace one frame at a time
+#[derive(Default)]
+struct DepthTracker {
+    /// Depth of each word still placed, and the deepest word placed below it
+    open: Vec<(usize, usize)>,
+    deepest: usize,
+}
+
+impl DepthTracker {
+    fn frame(&mut self, frame: &Frame) {
+        match frame {
+            Frame::Insert { depth, .. } => self.open.push((*depth, *depth)),
+            Frame::Remove { .. } => {
+                if let Some((depth, below)) = self.open.pop() {
+                    self.deepest = self.deepest.max(below - depth + 1);
+                    if let Some(parent) = self.open.last_mut() {
+                        parent.1 = parent.1.max(below);
+                    }
+                }
+            }
+            Frame::Candidates { .. } | Frame::Solved => {}
+        }
+    }
+}
+
+/// Rate the difficulty of a board, ignoring any words already placed
+///
+/// Returns `None` if the board has no solution.
+pub fn rate(
+    board: &Board,
+    freq: &Frequencies,
+    deadline: Option<Instant>,
+) -> Result<Option<Difficulty>, TimedOut> {
+    let mut solver = board.clone();
+    solver.reset();
+    solver.reset_stats();
+    let mut tracker = DepthTracker::default();
+    if !solver.solve_traced(deadline, &mut |f| tracker.frame(&f))? {
+        return Ok(None);
+    }
+    let words = solver.words();
+
+    // Walk the solution, counting the candidates before each word and those
+    // a person could rule out by looking at the board
+    let mut walk = board.clone();
+    walk.reset();
+    let mut branching = Vec::with_capacity(words.len());
+    let mut survivors = Vec::with_capacity(words.len());
+    for word in &words {
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            return Err(TimedOut);
+        }
+        branching.push(walk.next_words().len());
+        survivors.push(deduce::survivors(&walk).len());
+        walk.insert_word(&word.points);
+    }
+
+    let forced = survivors.iter().filter(|&&n| n <= 1).count();
+    let guesses = words.len() - forced;
+    let rarity = words
+        .iter()
+        .map(|w| freq.rarity(&w.word))
+        .sum::<Option<f64>>()
+        .map(|sum| sum / words.len() as f64);
+    let depth = tracker.deepest;
+
+    let mean_branching = branching.iter().sum::<usize>() as f64 / branching.len() as f64;
+    // Scaled so puzzles with a unique solution spread over every band: they
+    // rarely need a guess for more than two words in five, offer more than
+    // four candidates a step, or go more than 16 words down a wrong path
+    let score = 40.0 * (guesses as f64 / words.len() as f64 / 0.4).min(1.0)
+        + 20.0 * (mean_branching.ln() / 4f64.ln()).clamp(0.0, 1.0)
+        + 20.0 * (depth as f64 / 16.0).min(1.0);
+    // Without a frequency list the other terms make up the whole score
+    let score = match rarity {
+        Some(rarity) => score + 20.0 * rarity,
+        None => score * 100.0 / 80.0,
+    };
+    let score = (score * 10.0).round() / 10.0;
+
+    Ok(Some(Difficulty {
+        score,
+        band: Band::of(score),
+        branching,
+        survivors,
+        forced,
+        guesses,
+        backtrack_depth: depth,
+        backtracks: solver.stats().backtracks,
+        rarity,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+    use crate::{Point, PointSet};
+
+    #[test]
+    fn rates_a_solvable_board() {
+        let board = Board::try_from(GRID).unwrap();
+        let rating = rate(&board, &Frequencies::default(), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rating.forced + rating.guesses, rating.branching.len());
+        assert!(rating
+            .survivors
+            .iter()
+            .zip(&rating.branching)
+            .all(|(&v, &b)| v >= 1 && v <= b));
+        assert_eq!(
+            rating.guesses,
+            rating.survivors.iter().filter(|&&n| n > 1).count()
+        );
+        assert!((0.0..=100.0).contains(&rating.score));
+        assert_eq!(rating.band, Band::of(rating.score));
+        assert_eq!(rating.rarity, None);
+        // This board has many solutions, so some choices must be guesses
+        assert!(rating.guesses > 0);
+
+        let freq = Frequencies::parse("flat\ncarpet\n");
+        let rated = rate(&board, &freq, None).unwrap().unwrap();
+        assert!(rated.rarity.is_some_and(|r| (0.0..=1.0).contains(&r)));
+    }
+
+    #[test]
+    fn depth_of_wrong_paths() {
+        let points = PointSet::new(Point::new(0, 0));
+        let insert = |depth| Frame::Insert {
+            depth,
+            word: String::new(),
+            points: points.clone(),
+        };
+        let remove = |depth| Frame::Remove {
+            depth,
+            points: points.clone(),
+        };
+        let frames = [
+            insert(0),
+            insert(1),
+            insert(2),
+            remove(2),
+            remove(1),
+            insert(1),
+            remove(1),
+            remove(0),
+            insert(0),
+            Frame::Solved,
+        ];
+        let depth = |frames: &[Frame]| {
+            let mut tracker = DepthTracker::default();
+            frames.iter().for_each(|f| tracker.frame(f));
+            tracker.deepest
+        };
+        assert_eq!(depth(&frames), 3);
+        assert_eq!(depth(&[insert(0), Frame::Solved]), 0);
+    }
+}
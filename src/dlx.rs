@@ -0,0 +1,391 @@
+use crate::board::{SearchStats, TimedOut};
+use crate::{Board, Point, PointSet};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+#[cfg(feature = "cli")]
+use serde::Serialize;
+use std::ops::ControlFlow;
+use std::time::Instant;
+
+/// Ways of searching for solutions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum Engine {
+    /// Place words one at a time in reading order, backing out of dead ends
+    #[default]
+    Backtrack,
+    /// Solve the board as an exact cover problem with dancing links
+    Dlx,
+}
+
+impl Engine {
+    /// Solve the board, giving up once the deadline has passed
+    pub fn solve_until(
+        self,
+        board: &mut Board,
+        deadline: Option<Instant>,
+    ) -> Result<bool, TimedOut> {
+        match self {
+            Engine::Backtrack => board.solve_until(deadline),
+            Engine::Dlx => solve_until(board, deadline),
+        }
+    }
+
+    /// Call `found` with each solution as it is found, see
+    /// `Board::enumerate_solutions_until`
+    pub fn enumerate_until(
+        self,
+        board: &mut Board,
+        deadline: Option<Instant>,
+        found: &mut dyn FnMut(&Board) -> ControlFlow<()>,
+    ) -> Result<(), TimedOut> {
+        match self {
+            Engine::Backtrack => board.enumerate_solutions_until(deadline, found),
+            Engine::Dlx => enumerate_until(board, deadline, found),
+        }
+    }
+}
+
+/// A node in the dancing links matrix
+///
+/// Nodes refer to each other by index. Node 0 is the root, followed by one
+/// header per column, then the nodes of each row.
+#[derive(Clone, Copy, Default)]
+struct Node {
+    left: usize,
+    right: usize,
+    up: usize,
+    down: usize,
+    column: usize,
+    /// Index of the placement the node belongs to, unused for headers
+    row: usize,
+}
+
+/// The exact cover problem for a board: each free cell is a column, and each
+/// way a word could be placed is a row covering its cells
+///
+/// The reading order rule is built into the rows. Every placement is a word
+/// `list_words` would offer if its first cell were the root, so the words of
+/// any exact cover can be placed one after another in order of their first
+/// cell, and each cover is exactly one of the backtracker's solutions.
+struct Matrix {
+    nodes: Vec<Node>,
+    /// Number of rows still covering each column, indexed by header
+    sizes: Vec<usize>,
+    placements: Vec<PointSet>,
+    stats: SearchStats,
+}
+
+impl Matrix {
+    fn new(board: &Board) -> Matrix {
+        // Number the free cells, which are the columns
+        let mut columns = [[0usize; 7]; 12];
+        let mut n_columns = 0;
+        for (y, row) in columns.iter_mut().enumerate() {
+            for (x, column) in row.iter_mut().enumerate() {
+                if board.word_id(Point::new(x as u8, y as u8)).is_none() {
+                    n_columns += 1;
+                    *column = n_columns;
+                }
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(n_columns + 1);
+        for i in 0..=n_columns {
+            nodes.push(Node {
+                left: if i == 0 { n_columns } else { i - 1 },
+                right: if i == n_columns { 0 } else { i + 1 },
+                up: i,
+                down: i,
+                column: i,
+                row: 0,
+            });
+        }
+        let mut sizes = vec![0; n_columns + 1];
+
+        let placements = board.all_words();
+        for (row, placement) in placements.iter().enumerate() {
+            let first = nodes.len();
+            let cells = placement.points();
+            for (i, p) in cells.iter().enumerate() {
+                let column = columns[p.y as usize][p.x as usize];
+                let index = nodes.len();
+                let up = nodes[column].up;
+                nodes.push(Node {
+                    left: if i == 0 {
+                        first + cells.len() - 1
+                    } else {
+                        index - 1
+                    },
+                    right: if i == cells.len() - 1 {
+                        first
+                    } else {
+                        index + 1
+                    },
+                    up,
+                    down: column,
+                    column,
+                    row,
+                });
+                nodes[up].down = index;
+                nodes[column].up = index;
+                sizes[column] += 1;
+            }
+        }
+
+        Matrix {
+            nodes,
+            sizes,
+            placements,
+            stats: SearchStats::default(),
+        }
+    }
+
+    fn cover(&mut self, column: usize) {
+        let Node { left, right, .. } = self.nodes[column];
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+        let mut i = self.nodes[column].down;
+        while i != column {
+            let mut j = self.nodes[i].right;
+            while j != i {
+                let Node { up, down, .. } = self.nodes[j];
+                self.nodes[up].down = down;
+                self.nodes[down].up = up;
+                self.sizes[self.nodes[j].column] -= 1;
+                j = self.nodes[j].right;
+            }
+            i = self.nodes[i].down;
+        }
+    }
+
+    fn uncover(&mut self, column: usize) {
+        let mut i = self.nodes[column].up;
+        while i != column {
+            let mut j = self.nodes[i].left;
+            while j != i {
+                let Node { up, down, .. } = self.nodes[j];
+                self.nodes[up].down = j;
+                self.nodes[down].up = j;
+                self.sizes[self.nodes[j].column] += 1;
+                j = self.nodes[j].left;
+            }
+            i = self.nodes[i].up;
+        }
+        let Node { left, right, .. } = self.nodes[column];
+        self.nodes[left].right = column;
+        self.nodes[right].left = column;
+    }
+
+    /// Algorithm X, always branching on the column with the fewest rows
+    fn search(
+        &mut self,
+        chosen: &mut Vec<usize>,
+        deadline: Option<Instant>,
+        found: &mut dyn FnMut(&[usize]) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, TimedOut> {
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            return Err(TimedOut);
+        }
+        if self.nodes[0].right == 0 {
+            return Ok(found(chosen));
+        }
+        let mut column = self.nodes[0].right;
+        let mut best = column;
+        while column != 0 {
+            if self.sizes[column] < self.sizes[best] {
+                best = column;
+            }
+            column = self.nodes[column].right;
+        }
+        if self.sizes[best] == 0 {
+            return Ok(ControlFlow::Continue(()));
+        }
+
+        self.cover(best);
+        let mut i = self.nodes[best].down;
+        let mut res = Ok(ControlFlow::Continue(()));
+        while i != best {
+            chosen.push(self.nodes[i].row);
+            self.stats.nodes += 1;
+            let mut j = self.nodes[i].right;
+            while j != i {
+                self.cover(self.nodes[j].column);
+                j = self.nodes[j].right;
+            }
+            res = self.search(chosen, deadline, found);
+            let mut j = self.nodes[i].left;
+            while j != i {
+                self.uncover(self.nodes[j].column);
+                j = self.nodes[j].left;
+            }
+            chosen.pop();
+            self.stats.backtracks += 1;
+            if res != Ok(ControlFlow::Continue(())) {
+                break;
+            }
+            i = self.nodes[i].down;
+        }
+        self.uncover(best);
+        res
+    }
+}
+
+/// Place an exact cover of the free cells on the board, in order of each
+/// word's first cell so they're numbered as the backtracker would number them
+pub fn place_cover(board: &Board, cover: &[&PointSet]) -> Board {
+    let mut words = cover.to_vec();
+    words.sort_by_key(|w| (w.points()[0].y, w.points()[0].x));
+    let mut board = board.clone();
+    for word in words {
+        board.insert_word(word);
+    }
+    board
+}
+
+/// Call `found` with each solution, found by dancing links
+///
+/// Words already on the board are kept. Solutions come in a different order
+/// to the backtracker's.
+pub fn enumerate_until(
+    board: &mut Board,
+    deadline: Option<Instant>,
+    found: &mut dyn FnMut(&Board) -> ControlFlow<()>,
+) -> Result<(), TimedOut> {
+    let mut matrix = Matrix::new(board);
+    let placements = matrix.placements.clone();
+    let start = board.clone();
+    let res = matrix.search(&mut Vec::new(), deadline, &mut |rows| {
+        let words: Vec<&PointSet> = rows.iter().map(|&r| &placements[r]).collect();
+        let solution = place_cover(&start, &words);
+        if solution.is_solution() {
+            found(&solution)
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    board.add_stats(matrix.stats);
+    res.map(|_| ())
+}
+
+/// Solve the board by dancing links, giving up once the deadline has passed
+///
+/// On a timeout the board is left as it was before the call.
+pub fn solve_until(board: &mut Board, deadline: Option<Instant>) -> Result<bool, TimedOut> {
+    let mut solution = None;
+    enumerate_until(board, deadline, &mut |found| {
+        solution = Some(found.clone());
+        ControlFlow::Break(())
+    })?;
+    match solution {
+        Some(solution) => {
+            let stats = board.stats();
+            *board = solution;
+            board.reset_stats();
+            board.add_stats(stats);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// How the solutions found by each engine compare
+#[cfg(feature = "cli")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Agreement {
+    /// Number of solutions found by backtracking
+    pub backtrack: usize,
+    /// Number of solutions found by dancing links
+    pub dlx: usize,
+    /// True if both engines found exactly the same solutions
+    pub agree: bool,
+}
+
+/// Find every solution with both engines and compare them
+#[cfg(feature = "cli")]
+pub fn crosscheck(board: &Board, deadline: Option<Instant>) -> Result<Agreement, TimedOut> {
+    let mut found = [Vec::new(), Vec::new()];
+    for (engine, solutions) in [Engine::Backtrack, Engine::Dlx].into_iter().zip(&mut found) {
+        engine.enumerate_until(&mut board.clone(), deadline, &mut |s| {
+            solutions.push(s.clone());
+            ControlFlow::Continue(())
+        })?;
+    }
+    let [backtrack, dlx] = found;
+    let agree = backtrack.len() == dlx.len()
+        && dlx
+            .iter()
+            .all(|d| backtrack.iter().any(|b| b.same_solution(d)));
+    Ok(Agreement {
+        backtrack: backtrack.len(),
+        dlx: dlx.len(),
+        agree,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+
+    fn all(engine: Engine, board: &Board) -> Vec<Board> {
+        let mut board = board.clone();
+        let mut solutions = Vec::new();
+        engine
+            .enumerate_until(&mut board, None, &mut |s| {
+                solutions.push(s.clone());
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        solutions
+    }
+
+    #[test]
+    fn finds_the_same_solutions_as_backtracking() {
+        let board = Board::try_from(GRID).unwrap();
+        let backtrack = all(Engine::Backtrack, &board);
+        let dlx = all(Engine::Dlx, &board);
+        assert_eq!(backtrack.len(), dlx.len());
+        for solution in &dlx {
+            assert!(backtrack.iter().any(|b| b.same_solution(solution)));
+            // Words are numbered in reading order, as if placed by the solver
+            assert_eq!(
+                board.check_words(
+                    &solution
+                        .words()
+                        .iter()
+                        .map(|w| w.points.clone())
+                        .collect::<Vec<_>>()
+                ),
+                Ok(())
+            );
+        }
+
+        let mut solved = board.clone();
+        assert!(Engine::Dlx.solve_until(&mut solved, None).unwrap());
+        assert!(solved.is_done());
+        assert!(solved.stats().nodes > 0);
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn crosscheck_agrees() {
+        let board = Board::try_from(GRID).unwrap();
+        assert!(crosscheck(&board, None).unwrap().agree);
+    }
+
+    #[test]
+    fn keeps_placed_words() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let first = board
+            .next_words()
+            .into_iter()
+            .find(|w| board.spell(w) == "flat")
+            .unwrap();
+        board.insert_word(&first);
+        let backtrack = all(Engine::Backtrack, &board);
+        let dlx = all(Engine::Dlx, &board);
+        assert_eq!(backtrack.len(), dlx.len());
+        assert!(dlx.iter().all(|s| s.words()[0].word == "flat"));
+    }
+}
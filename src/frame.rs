@@ -0,0 +1,58 @@
+use crate::{Point, PointSet};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Why a partial word was given up on while listing candidates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reason {
+    /// No word in the dictionary starts with these letters
+    NoSuchWord,
+    /// No cells added later could join the points up
+    NotConnectable,
+    /// The letters spell a word, but the cells don't touch
+    NotContiguous,
+    /// The word is ruled out by a constraint
+    Constrained,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::NoSuchWord => write!(f, "no such word"),
+            Reason::NotConnectable => write!(f, "can't be connected"),
+            Reason::NotContiguous => write!(f, "not contiguous"),
+            Reason::Constrained => write!(f, "ruled out by a constraint"),
+        }
+    }
+}
+
+/// A partial word, and why it went no further
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rejected {
+    pub points: PointSet,
+    pub reason: Reason,
+}
+
+/// One step of the search
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum Frame {
+    /// The words that could be placed next, starting at the first free cell
+    Candidates {
+        depth: usize,
+        root: Point,
+        words: Vec<PointSet>,
+        rejected: Vec<Rejected>,
+    },
+    /// A word was placed with `insert_word`
+    Insert {
+        depth: usize,
+        word: String,
+        points: PointSet,
+    },
+    /// A word was taken back with `remove_word`, as nothing followed from it
+    Remove { depth: usize, points: PointSet },
+    /// The board was filled in
+    Solved,
+}
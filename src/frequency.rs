@@ -0,0 +1,109 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A third of the range of rarity, from the most to the least common words
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+impl Tier {
+    pub fn of(rarity: f64) -> Tier {
+        if rarity < 1.0 / 3.0 {
+            Tier::Common
+        } else if rarity < 2.0 / 3.0 {
+            Tier::Uncommon
+        } else {
+            Tier::Rare
+        }
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Tier::Common => "common",
+            Tier::Uncommon => "uncommon",
+            Tier::Rare => "rare",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How common words are, used to judge how hard a word is to spot
+///
+/// With a list of words ordered from most to least common, a word's rarity
+/// is its position in the list. The default has no list, so rarity is
+/// unknown.
+#[derive(Clone, Debug, Default)]
+pub struct Frequencies {
+    ranks: Option<HashMap<String, usize>>,
+}
+
+impl Frequencies {
+    /// Read words, most common first, one per line
+    ///
+    /// Anything after the first word on a line, such as a count, is ignored,
+    /// as are blank lines and lines starting with `#`.
+    pub fn parse(text: &str) -> Frequencies {
+        let mut ranks = HashMap::new();
+        let words = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_whitespace().next());
+        for word in words {
+            let rank = ranks.len();
+            ranks.entry(word.to_lowercase()).or_insert(rank);
+        }
+        Frequencies { ranks: Some(ranks) }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Frequencies> {
+        Ok(Frequencies::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Whether there's a list to judge rarity by
+    pub fn has_list(&self) -> bool {
+        self.ranks.is_some()
+    }
+
+    /// How rare a word is, from 0 for the most common to 1 for the rarest,
+    /// or `None` without a list
+    ///
+    /// Words missing from the list count as the rarest of all.
+    pub fn rarity(&self, word: &str) -> Option<f64> {
+        let ranks = self.ranks.as_ref()?;
+        Some(match ranks.get(&word.to_lowercase()) {
+            Some(&rank) => rank as f64 / ranks.len().max(1) as f64,
+            None => 1.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranked_words() {
+        let freq = Frequencies::parse("# most common first\nthe 100\nof 90\n\nTower 3\ncell\n");
+        assert_eq!(freq.rarity("the"), Some(0.0));
+        assert_eq!(freq.rarity("tower"), Some(0.5));
+        assert_eq!(freq.rarity("zyzzyva"), Some(1.0));
+    }
+
+    #[test]
+    fn unknown_without_a_list() {
+        assert!(!Frequencies::default().has_list());
+        assert_eq!(Frequencies::default().rarity("tower"), None);
+    }
+}
@@ -0,0 +1,676 @@
+use crate::board::TimedOut;
+use crate::dictionary::Dictionary;
+use crate::difficulty::{self, Band, Difficulty};
+use crate::frequency::{Frequencies, Tier};
+use crate::{Board, Point, PointSet, Puzzle};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::ops::ControlFlow;
+use std::time::Instant;
+
+/// A small, seedable random number generator, so a puzzle can be made again
+/// from its recipe
+///
+/// This is splitmix64, which is plenty for picking shapes and words.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to, but not including, `n`
+    pub fn below(&mut self, n: usize) -> usize {
+        debug_assert!(n > 0);
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number from `low` to `high` inclusive
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high - low + 1)
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.below(items.len())])
+        }
+    }
+}
+
+/// Everything which decides what puzzle is made
+///
+/// A generated puzzle keeps its recipe, so it can be made again from that
+/// alone, given the same dictionary and word frequencies.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recipe {
+    pub seed: u64,
+    #[serde(rename = "minSize")]
+    pub min_size: usize,
+    #[serde(rename = "maxSize")]
+    pub max_size: usize,
+    /// Only keep puzzles rated in this band
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub band: Option<Band>,
+    /// Words which must all appear in the puzzle
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub theme: Vec<String>,
+    /// Draw the other words only from this tier of the frequency list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tier: Option<Tier>,
+}
+
+impl Recipe {
+    pub fn new(seed: u64) -> Recipe {
+        Recipe {
+            seed,
+            min_size: 4,
+            max_size: 8,
+            band: None,
+            theme: Vec::new(),
+            tier: None,
+        }
+    }
+
+    /// The recipe saved in a generated puzzle, if it has one
+    pub fn of(puzzle: &Puzzle) -> Option<Recipe> {
+        serde_json::from_value(puzzle.extra.get("recipe")?.clone()).ok()
+    }
+}
+
+/// Limits on how hard to try when making a puzzle
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Boards to try before giving up
+    pub attempts: usize,
+    /// Give up checking a board for a unique solution after this many nodes,
+    /// as boards which take long to search are rarely unique
+    pub max_nodes: u64,
+    pub deadline: Option<Instant>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            attempts: 500,
+            max_nodes: 200_000,
+            deadline: None,
+        }
+    }
+}
+
+/// Reasons a puzzle couldn't be made
+#[derive(Debug, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The word lengths asked for can't be used
+    BadSizes {
+        min: usize,
+        max: usize,
+    },
+    /// The dictionary has no words of a length needed
+    NoWords {
+        length: usize,
+        tier: Option<Tier>,
+    },
+    /// A theme word isn't in the dictionary
+    NotAWord {
+        word: String,
+    },
+    /// A theme word is outside the word lengths allowed
+    ThemeLength {
+        word: String,
+    },
+    /// A frequency tier was asked for without a frequency list
+    NoFrequencies {
+        tier: Tier,
+    },
+    /// No board meeting the recipe turned up in time
+    GaveUp {
+        attempts: usize,
+    },
+    TimedOut,
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::BadSizes { min, max } => write!(
+                f,
+                "word lengths must be between 1 and 8, with the minimum no more than the maximum, not {} to {}",
+                min, max
+            ),
+            GenerateError::NoWords {
+                length,
+                tier: None,
+            } => write!(f, "the dictionary has no words with {} letters", length),
+            GenerateError::NoWords {
+                length,
+                tier: Some(tier),
+            } => write!(
+                f,
+                "the dictionary has no {} words with {} letters",
+                tier, length
+            ),
+            GenerateError::NotAWord { word } => {
+                write!(f, "theme word {} isn't in the dictionary", word)
+            }
+            GenerateError::ThemeLength { word } => write!(
+                f,
+                "theme word {} is shorter or longer than the words allowed",
+                word
+            ),
+            GenerateError::NoFrequencies { tier } => write!(
+                f,
+                "drawing {} words needs a word frequency list",
+                tier
+            ),
+            GenerateError::GaveUp { attempts } => write!(
+                f,
+                "no board meeting the recipe found after {} attempts",
+                attempts
+            ),
+            GenerateError::TimedOut => write!(f, "gave up after reaching the timeout"),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+impl From<TimedOut> for GenerateError {
+    fn from(_: TimedOut) -> GenerateError {
+        GenerateError::TimedOut
+    }
+}
+
+/// A new puzzle, and how it was made
+#[derive(Clone)]
+pub struct Generated {
+    pub puzzle: Puzzle,
+    pub recipe: Recipe,
+    /// Boards tried, including the one kept
+    pub attempts: usize,
+    /// The puzzle's rating, when a band was asked for
+    pub difficulty: Option<Difficulty>,
+}
+
+/// Pick a random region of `length` free cells starting at `root`, which
+/// the solver could read as a word
+///
+/// Cells are added the way `list_words` adds them: to the right on the same
+/// row, or on the next row no further right than the last cell.
+fn random_shape(
+    free: &[[bool; 7]; 12],
+    root: Point,
+    length: usize,
+    rng: &mut Rng,
+) -> Option<PointSet> {
+    let mut points = PointSet::new(root);
+    while points.length() < length {
+        let last = points.last_point();
+        let mut options = Vec::new();
+        for x in last.x + 1..7 {
+            options.push(Point::new(x, last.y));
+        }
+        if last.y < 11 {
+            for x in 0..=last.x {
+                options.push(Point::new(x, last.y + 1));
+            }
+        }
+        let options: Vec<PointSet> = options
+            .into_iter()
+            .filter(|p| free[p.y as usize][p.x as usize])
+            .map(|p| {
+                let mut next = points.clone();
+                next.push(p);
+                next
+            })
+            .filter(PointSet::connectable)
+            .collect();
+        // Prefer cells which keep the region joined up as it grows
+        let joined: Vec<PointSet> = options.iter().filter(|p| p.contiguous()).cloned().collect();
+        points = if joined.is_empty() {
+            rng.pick(&options)?.clone()
+        } else {
+            rng.pick(&joined)?.clone()
+        };
+    }
+    points.contiguous().then_some(points)
+}
+
+fn first_free(free: &[[bool; 7]; 12]) -> Option<Point> {
+    free.iter().enumerate().find_map(|(y, row)| {
+        row.iter()
+            .position(|&f| f)
+            .map(|x| Point::new(x as u8, y as u8))
+    })
+}
+
+/// Randomly cover the board with regions, in reading order, backtracking when
+/// a region leaves cells that can't be covered
+///
+/// Each region has between `min_size` and `max_size` cells. Returns `None`
+/// if `budget` runs out first.
+pub fn random_tiling(
+    min_size: usize,
+    max_size: usize,
+    rng: &mut Rng,
+    budget: &mut usize,
+) -> Option<Vec<PointSet>> {
+    let mut free = [[true; 7]; 12];
+    let mut regions = Vec::new();
+    tile(&mut free, min_size, max_size, rng, budget, &mut regions).then_some(regions)
+}
+
+fn tile(
+    free: &mut [[bool; 7]; 12],
+    min_size: usize,
+    max_size: usize,
+    rng: &mut Rng,
+    budget: &mut usize,
+    regions: &mut Vec<PointSet>,
+) -> bool {
+    let Some(root) = first_free(free) else {
+        return true;
+    };
+    for _ in 0..8 {
+        if *budget == 0 {
+            return false;
+        }
+        *budget -= 1;
+        let length = rng.range(min_size, max_size);
+        let Some(shape) = random_shape(free, root, length, rng) else {
+            continue;
+        };
+        for p in shape.points() {
+            free[p.y as usize][p.x as usize] = false;
+        }
+        regions.push(shape);
+        if tile(free, min_size, max_size, rng, budget, regions) {
+            return true;
+        }
+        let shape = regions.pop().unwrap();
+        for p in shape.points() {
+            free[p.y as usize][p.x as usize] = true;
+        }
+    }
+    false
+}
+
+/// Build a puzzle from regions and the words written into them
+///
+/// Each word is written into its region in reading order.
+pub fn puzzle_from(
+    regions: &[PointSet],
+    words: &[String],
+    min_size: usize,
+    max_size: usize,
+) -> Puzzle {
+    Puzzle {
+        width: 7,
+        height: 12,
+        min_size,
+        max_size,
+        regions: regions
+            .iter()
+            .map(|region| {
+                region
+                    .sorted()
+                    .points()
+                    .iter()
+                    .map(|p| [p.x as usize, p.y as usize])
+                    .collect()
+            })
+            .collect(),
+        words: words.to_vec(),
+        extra: Map::new(),
+    }
+}
+
+/// Count solutions to a board, stopping at two
+///
+/// Returns `None` if the search was cut off after `max_nodes` words before
+/// settling it.
+pub fn count_up_to_two(
+    board: &mut Board,
+    max_nodes: u64,
+    deadline: Option<Instant>,
+) -> Result<Option<usize>, TimedOut> {
+    let mut found = 0;
+    board.reset_stats();
+    let finished = board.enumerate_solutions_within(deadline, max_nodes, &mut |_| {
+        found += 1;
+        if found >= 2 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })?;
+    Ok(finished.then_some(found))
+}
+
+/// Words in the dictionary, grouped by length, keeping only those in the
+/// given frequency tier
+pub fn words_by_length(
+    dict: &Dictionary,
+    freq: &Frequencies,
+    tier: Option<Tier>,
+) -> Vec<Vec<String>> {
+    let mut by_length = vec![Vec::new(); 9];
+    for word in dict.words() {
+        if word.len() <= 8 && tier.is_none_or(|t| freq.rarity(&word).map(Tier::of) == Some(t)) {
+            by_length[word.len()].push(word);
+        }
+    }
+    by_length
+}
+
+/// Choose a word for each region, putting each theme word into a random
+/// region of its length
+///
+/// Returns `None` if the regions don't have room for every theme word.
+fn fill(
+    regions: &[PointSet],
+    theme: &[String],
+    by_length: &[Vec<String>],
+    rng: &mut Rng,
+) -> Option<Vec<String>> {
+    let mut words: Vec<Option<String>> = vec![None; regions.len()];
+    for word in theme {
+        let open: Vec<usize> = (0..regions.len())
+            .filter(|&i| words[i].is_none() && regions[i].length() == word.len())
+            .collect();
+        words[*rng.pick(&open)?] = Some(word.clone());
+    }
+    Some(
+        words
+            .into_iter()
+            .zip(regions)
+            .map(|(word, region)| {
+                word.unwrap_or_else(|| rng.pick(&by_length[region.length()]).unwrap().clone())
+            })
+            .collect(),
+    )
+}
+
+/// Check a recipe can be followed before trying to
+fn check(recipe: &Recipe, dict: &Dictionary, freq: &Frequencies) -> Result<(), GenerateError> {
+    let (min, max) = (recipe.min_size, recipe.max_size);
+    if min == 0 || min > max || max > 8 {
+        return Err(GenerateError::BadSizes { min, max });
+    }
+    for word in &recipe.theme {
+        let letters = word.bytes().all(|c| c.is_ascii_lowercase());
+        if !letters || !dict.is_word(word.as_bytes()) {
+            return Err(GenerateError::NotAWord { word: word.clone() });
+        }
+        if !(min..=max).contains(&word.len()) {
+            return Err(GenerateError::ThemeLength { word: word.clone() });
+        }
+    }
+    if let (Some(tier), false) = (recipe.tier, freq.has_list()) {
+        return Err(GenerateError::NoFrequencies { tier });
+    }
+    Ok(())
+}
+
+/// Make a new puzzle with a unique solution, following a recipe
+///
+/// The same recipe, dictionary and word frequencies always give the same
+/// puzzle, as long as the limits in `options` aren't reached.
+pub fn generate(
+    dict: Dictionary,
+    freq: &Frequencies,
+    recipe: &Recipe,
+    options: &Options,
+) -> Result<Generated, GenerateError> {
+    let mut recipe = recipe.clone();
+    for word in &mut recipe.theme {
+        *word = word.to_lowercase();
+    }
+    check(&recipe, &dict, freq)?;
+    let (min, max) = (recipe.min_size, recipe.max_size);
+    let by_length = words_by_length(&dict, freq, recipe.tier);
+    if let Some(length) = (min..=max).find(|&n| by_length[n].is_empty()) {
+        return Err(GenerateError::NoWords {
+            length,
+            tier: recipe.tier,
+        });
+    }
+
+    let mut rng = Rng::new(recipe.seed);
+    for attempt in 1..=options.attempts {
+        if options.deadline.is_some_and(|d| Instant::now() > d) {
+            return Err(GenerateError::TimedOut);
+        }
+        let mut budget = 10_000;
+        let Some(regions) = random_tiling(min, max, &mut rng, &mut budget) else {
+            continue;
+        };
+        let Some(words) = fill(&regions, &recipe.theme, &by_length, &mut rng) else {
+            continue;
+        };
+        let mut puzzle = puzzle_from(&regions, &words, min, max);
+        let mut board = Board::try_from(puzzle.clone()).expect("generated puzzles are well formed");
+        board.set_dictionary(dict);
+        if count_up_to_two(&mut board, options.max_nodes, options.deadline)? != Some(1) {
+            continue;
+        }
+        let difficulty = match recipe.band {
+            Some(band) => match difficulty::rate(&board, freq, options.deadline)? {
+                Some(rating) if rating.band == band => Some(rating),
+                _ => continue,
+            },
+            None => None,
+        };
+        puzzle.extra.insert(
+            "title".to_string(),
+            Value::String(format!("Generated from seed {}", recipe.seed)),
+        );
+        puzzle
+            .extra
+            .insert("recipe".to_string(), serde_json::to_value(&recipe).unwrap());
+        return Ok(Generated {
+            puzzle,
+            recipe,
+            attempts: attempt,
+            difficulty,
+        });
+    }
+    Err(GenerateError::GaveUp {
+        attempts: options.attempts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+
+    #[test]
+    fn tilings_are_readable() {
+        let mut rng = Rng::new(7);
+        let mut tiled = 0;
+        for _ in 0..20 {
+            let mut budget = 10_000;
+            // Some attempts paint themselves into a corner, and are retried
+            let Some(regions) = random_tiling(4, 8, &mut rng, &mut budget) else {
+                continue;
+            };
+            tiled += 1;
+            let mut seen = 0;
+            let mut board = Board::try_from("aaaaaaa\n".repeat(12).as_str()).unwrap();
+            for region in &regions {
+                assert!((4..=8).contains(&region.length()));
+                assert!(region.contiguous());
+                // Each region starts at the first free cell, so the solver
+                // would reach it in this order
+                assert_eq!(Some(region.sorted().points()[0]), board.get_root());
+                board.insert_word(region);
+                seen += region.length();
+            }
+            assert_eq!(seen, 84);
+            assert!(board.is_done());
+        }
+        assert!(tiled >= 10);
+    }
+
+    #[test]
+    fn generated_puzzle_is_unique_and_reproducible() {
+        let options = Options {
+            attempts: 2000,
+            ..Options::default()
+        };
+        let dict = Dictionary::builtin();
+        let freq = Frequencies::default();
+        let generated = generate(dict, &freq, &Recipe::new(42), &options).unwrap();
+        let puzzle = &generated.puzzle;
+        assert_eq!(puzzle.regions.len(), puzzle.words.len());
+        for word in &puzzle.words {
+            assert!(dict.is_word(word.as_bytes()));
+        }
+
+        // The puzzle survives a trip through json, and its only solution is
+        // the official one
+        let json = serde_json::to_string(puzzle).unwrap();
+        let puzzle: Puzzle = serde_json::from_str(&json).unwrap();
+        let mut board = Board::try_from(puzzle.clone()).unwrap();
+        let solutions = board.enumerate_solutions();
+        assert_eq!(solutions.len(), 1);
+        assert!(solutions[0].same_solution(&Board::official_solution(&puzzle).unwrap()));
+
+        // The recipe saved in the puzzle is enough to make it again
+        let recipe = Recipe::of(&puzzle).unwrap();
+        assert_eq!(recipe, Recipe::new(42));
+        let again = generate(dict, &freq, &recipe, &options).unwrap();
+        assert_eq!(again.puzzle.words, generated.puzzle.words);
+        assert_eq!(again.puzzle.regions, generated.puzzle.regions);
+    }
+
+    #[test]
+    fn follows_theme_tier_and_band() {
+        let dict = Dictionary::builtin();
+        // Rank the dictionary's words as they come, to have tiers to draw from
+        let freq = Frequencies::parse(&dict.words().join("\n"));
+        let recipe = Recipe {
+            band: Some(Band::Medium),
+            theme: vec!["Tower".to_string(), "signal".to_string()],
+            tier: Some(Tier::Common),
+            ..Recipe::new(3)
+        };
+        let options = Options {
+            attempts: 2000,
+            ..Options::default()
+        };
+        let generated = generate(dict, &freq, &recipe, &options).unwrap();
+        let words = &generated.puzzle.words;
+        assert!(words.contains(&"tower".to_string()));
+        assert!(words.contains(&"signal".to_string()));
+        for word in words {
+            if word != "tower" && word != "signal" {
+                assert_eq!(freq.rarity(word).map(Tier::of), Some(Tier::Common));
+            }
+        }
+        assert_eq!(generated.difficulty.unwrap().band, Band::Medium);
+        assert_eq!(generated.recipe.theme, ["tower", "signal"]);
+    }
+
+    #[test]
+    fn reaches_the_hardest_band() {
+        let recipe = Recipe {
+            band: Some(Band::Fiendish),
+            ..Recipe::new(9)
+        };
+        let generated = generate(
+            Dictionary::builtin(),
+            &Frequencies::default(),
+            &recipe,
+            &Options::default(),
+        )
+        .unwrap();
+        assert_eq!(generated.difficulty.unwrap().band, Band::Fiendish);
+    }
+
+    #[test]
+    fn node_budget_only_gives_up_when_cut_off() {
+        let mut board = Board::try_from(GRID).unwrap();
+        assert_eq!(count_up_to_two(&mut board, 0, None), Ok(None));
+        assert_eq!(count_up_to_two(&mut board, u64::MAX, None), Ok(Some(2)));
+
+        // With most of a solution already placed only that solution is
+        // left. A search which settles it within exactly its budget still
+        // counts, and one node short of that it gives up.
+        let mut solved = Board::try_from(GRID).unwrap();
+        assert!(solved.solve());
+        for word in &solved.words()[..8] {
+            board.insert_word(&word.points);
+        }
+        assert_eq!(count_up_to_two(&mut board, u64::MAX, None), Ok(Some(1)));
+        let nodes = board.stats().nodes;
+        assert_eq!(count_up_to_two(&mut board, nodes, None), Ok(Some(1)));
+        assert_eq!(count_up_to_two(&mut board, nodes - 1, None), Ok(None));
+    }
+
+    #[test]
+    fn rejects_bad_recipes() {
+        let dict = Dictionary::builtin();
+        let freq = Frequencies::default();
+        let options = Options::default();
+        let try_recipe = |recipe: Recipe| generate(dict, &freq, &recipe, &options).err();
+        assert_eq!(
+            try_recipe(Recipe {
+                min_size: 6,
+                max_size: 5,
+                ..Recipe::new(1)
+            }),
+            Some(GenerateError::BadSizes { min: 6, max: 5 })
+        );
+        assert_eq!(
+            try_recipe(Recipe {
+                min_size: 2,
+                max_size: 3,
+                ..Recipe::new(1)
+            }),
+            Some(GenerateError::NoWords {
+                length: 2,
+                tier: None
+            })
+        );
+        assert_eq!(
+            try_recipe(Recipe {
+                max_size: 5,
+                theme: vec!["signal".to_string()],
+                ..Recipe::new(1)
+            }),
+            Some(GenerateError::ThemeLength {
+                word: "signal".to_string()
+            })
+        );
+        assert_eq!(
+            try_recipe(Recipe {
+                theme: vec!["ab1".to_string()],
+                ..Recipe::new(3)
+            }),
+            Some(GenerateError::NotAWord {
+                word: "ab1".to_string()
+            })
+        );
+        assert_eq!(
+            try_recipe(Recipe {
+                tier: Some(Tier::Rare),
+                ..Recipe::new(1)
+            }),
+            Some(GenerateError::NoFrequencies { tier: Tier::Rare })
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::board::PlacedWord;
+use crate::{Board, PointSet};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A word placed before solving, either by its cells or by its spelling
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Given {
+    /// A word spelled out, which must be traceable in exactly one way from
+    /// the next free cell
+    Word(String),
+    /// The cells of a word, as `[x, y]` pairs
+    Points(PointSet),
+    /// A word as printed by `solve --format json`
+    Placed { points: PointSet },
+}
+
+/// A file of given words: a list of them, or an object with a `words` list,
+/// such as the output of `solve --format json`
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum GivenFile {
+    List(Vec<Given>),
+    Report { words: Vec<Given> },
+}
+
+impl GivenFile {
+    pub fn into_words(self) -> Vec<Given> {
+        match self {
+            GivenFile::List(words) | GivenFile::Report { words } => words,
+        }
+    }
+}
+
+/// Why a given word couldn't be placed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GivenError {
+    /// Every cell was already covered
+    Full { index: usize },
+    /// No way of tracing the word starts at the next free cell
+    NotFound { index: usize, word: String },
+    /// The word could be traced from the next free cell in several ways
+    Ambiguous {
+        index: usize,
+        word: String,
+        ways: usize,
+    },
+    /// The cells aren't a legal next word
+    Illegal { index: usize, points: PointSet },
+}
+
+impl fmt::Display for GivenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GivenError::Full { index } => {
+                write!(f, "word {}: the board is already full", index + 1)
+            }
+            GivenError::NotFound { index, word } => write!(
+                f,
+                "word {}: {} can't be traced from the next free cell",
+                index + 1,
+                word.to_uppercase()
+            ),
+            GivenError::Ambiguous { index, word, ways } => write!(
+                f,
+                "word {}: {} can be traced {} ways from the next free cell, give its cells instead",
+                index + 1,
+                word.to_uppercase(),
+                ways
+            ),
+            GivenError::Illegal { index, points } => write!(
+                f,
+                "word {}: {} isn't a legal next word in reading order",
+                index + 1,
+                points
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GivenError {}
+
+/// Place the given words on the board, in order, checking each is a legal
+/// next word so the search can carry on from where they leave off
+///
+/// Nothing is placed unless every word is legal.
+pub fn place(board: &mut Board, given: &[Given]) -> Result<Vec<PlacedWord>, GivenError> {
+    let mut next = board.clone();
+    for (index, word) in given.iter().enumerate() {
+        if next.is_done() {
+            return Err(GivenError::Full { index });
+        }
+        let candidates = next.next_words();
+        let points = match word {
+            Given::Word(word) => {
+                let word = word.to_lowercase();
+                let mut matches = candidates.into_iter().filter(|c| next.spell(c) == word);
+                match (matches.next(), matches.count()) {
+                    (None, _) => return Err(GivenError::NotFound { index, word }),
+                    (Some(points), 0) => points,
+                    (Some(_), more) => {
+                        return Err(GivenError::Ambiguous {
+                            index,
+                            word,
+                            ways: more + 1,
+                        })
+                    }
+                }
+            }
+            Given::Points(points) | Given::Placed { points } => {
+                let points = points.sorted();
+                if !candidates.contains(&points) {
+                    return Err(GivenError::Illegal { index, points });
+                }
+                points
+            }
+        };
+        next.insert_word(&points);
+    }
+    *board = next;
+    Ok(board.words())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+    use crate::Point;
+
+    #[test]
+    fn places_words_and_cells() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let given: Vec<Given> = serde_json::from_str(
+            r#"["flat", [[4,0],[5,0],[6,0],[4,1],[5,1],[6,1]], {"id": 2, "points": [[0,1],[1,1],[2,1],[3,1]]}]"#,
+        )
+        .unwrap();
+        assert_eq!(given[0], Given::Word("flat".to_string()));
+        let placed = place(&mut board, &given).unwrap();
+        let words: Vec<&str> = placed.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(words, ["flat", "carpet", "snip"]);
+
+        // The search carries on from the given words
+        assert!(board.solve());
+        assert_eq!(board.words()[1].word, "carpet");
+
+        // Giving a whole solution leaves nothing to search, but still counts
+        let whole: Vec<Given> = board
+            .words()
+            .into_iter()
+            .map(|w| Given::Points(w.points))
+            .collect();
+        let mut board = Board::try_from(GRID).unwrap();
+        place(&mut board, &whole).unwrap();
+        assert_eq!(board.enumerate_solutions().len(), 1);
+    }
+
+    #[test]
+    fn rejects_illegal_prefixes() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let words = |words: &[&str]| -> Vec<Given> {
+            words.iter().map(|w| Given::Word(w.to_string())).collect()
+        };
+        assert_eq!(
+            place(&mut board, &words(&["flat", "spinner"])),
+            Err(GivenError::NotFound {
+                index: 1,
+                word: "spinner".to_string()
+            })
+        );
+        // Nothing was placed
+        assert_eq!(board.n_words(), 0);
+
+        let mut points = PointSet::empty();
+        for x in 0..4 {
+            points.push(Point::new(x, 1));
+        }
+        assert!(matches!(
+            place(&mut board, &[Given::Points(points)]),
+            Err(GivenError::Illegal { index: 0, .. })
+        ));
+    }
+}
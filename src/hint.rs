@@ -0,0 +1,185 @@
+use crate::board::TimedOut;
+use crate::{Board, PointSet};
+use std::time::Instant;
+
+/// How much a hint gives away, from least to most
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// The cell the next word starts at
+    Start,
+    /// How many letters the next word has
+    Length,
+    /// Which cells the next word covers
+    Cells,
+    /// The next word itself
+    Word,
+}
+
+impl Level {
+    /// The level after this one, if there is more to give away
+    pub fn next(self) -> Option<Level> {
+        match self {
+            Level::Start => Some(Level::Length),
+            Level::Length => Some(Level::Cells),
+            Level::Cells => Some(Level::Word),
+            Level::Word => None,
+        }
+    }
+}
+
+/// The next word of a solution that follows on from the words placed so far
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hint {
+    /// Cells of the word, in reading order
+    pub points: PointSet,
+    pub word: String,
+}
+
+impl Hint {
+    /// Solve a copy of the board, keeping the words already placed
+    ///
+    /// Returns `None` if the board is done, or if no solution follows from
+    /// the words placed so far.
+    pub fn find(board: &Board, deadline: Option<Instant>) -> Result<Option<Hint>, TimedOut> {
+        if board.is_done() {
+            return Ok(None);
+        }
+        let next = board.n_words();
+        let mut solved = board.clone();
+        if !solved.solve_until(deadline)? {
+            return Ok(None);
+        }
+        Ok(solved
+            .words()
+            .into_iter()
+            .find(|word| word.id == next)
+            .map(|word| Hint {
+                points: word.points,
+                word: word.word,
+            }))
+    }
+
+    /// Describe the hint, giving away everything up to the given level
+    pub fn describe(&self, level: Level) -> String {
+        let start = self.points.points()[0];
+        match level {
+            Level::Start => format!("The next word starts at {}", start),
+            Level::Length => format!(
+                "The next word starts at {} and has {} letters",
+                start,
+                self.points.length()
+            ),
+            Level::Cells => {
+                let cells: Vec<String> =
+                    self.points.points().iter().map(|p| p.to_string()).collect();
+                format!("The next word covers {}", cells.join(", "))
+            }
+            Level::Word => format!(
+                "The next word is {}, covering {}",
+                self.word.to_uppercase(),
+                self.points
+            ),
+        }
+    }
+}
+
+/// Hands out hints for the next word one level at a time
+///
+/// Call `reset` whenever the board changes, so the next hint starts again
+/// from the lowest level.
+#[derive(Default)]
+pub struct Hints {
+    current: Option<(Hint, Level)>,
+}
+
+impl Hints {
+    pub fn new() -> Hints {
+        Hints::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+
+    /// The hint being given and how much of it has been given away
+    pub fn current(&self) -> Option<(&Hint, Level)> {
+        self.current.as_ref().map(|(hint, level)| (hint, *level))
+    }
+
+    /// Give away a little more about the next word
+    ///
+    /// The board is only solved for the first hint about each word. Returns
+    /// `None` if there is nothing to hint at.
+    pub fn next(
+        &mut self,
+        board: &Board,
+        deadline: Option<Instant>,
+    ) -> Result<Option<(&Hint, Level)>, TimedOut> {
+        self.current = match self.current.take() {
+            Some((hint, level)) => Some((hint, level.next().unwrap_or(level))),
+            None => Hint::find(board, deadline)?.map(|hint| (hint, Level::Start)),
+        };
+        Ok(self.current())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+    use crate::Point;
+
+    #[test]
+    fn hints_follow_placed_words() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let first = Hint::find(&board, None).unwrap().unwrap();
+        assert_eq!(first.points.points()[0], Point::new(0, 0));
+
+        board.insert_word(&first.points);
+        let second = Hint::find(&board, None).unwrap().unwrap();
+        assert_eq!(Some(second.points.points()[0]), board.get_root());
+        // The search mustn't have touched the board it was given
+        assert_eq!(board.n_words(), 1);
+    }
+
+    #[test]
+    fn no_hint_after_a_dead_end() {
+        let mut board = Board::try_from(GRID).unwrap();
+        // Cut the top right cell off from everything else
+        let row = |y: u8, n: u8| {
+            let mut points = PointSet::new(Point::new(0, y));
+            for x in 1..n {
+                points.push(Point::new(x, y));
+            }
+            points
+        };
+        board.insert_word(&row(0, 6));
+        board.insert_word(&row(1, 7));
+        assert_eq!(Hint::find(&board, None), Ok(None));
+    }
+
+    #[test]
+    fn levels_give_away_more_each_time() {
+        let board = Board::try_from(GRID).unwrap();
+        let mut hints = Hints::new();
+        let levels: Vec<Level> = (0..5)
+            .map(|_| hints.next(&board, None).unwrap().unwrap().1)
+            .collect();
+        assert_eq!(
+            levels,
+            [
+                Level::Start,
+                Level::Length,
+                Level::Cells,
+                Level::Word,
+                Level::Word
+            ]
+        );
+        let (hint, _) = hints.current().unwrap();
+        assert!(hint
+            .describe(Level::Word)
+            .contains(&hint.word.to_uppercase()));
+        hints.reset();
+        assert_eq!(hints.next(&board, None).unwrap().unwrap().1, Level::Start);
+    }
+}
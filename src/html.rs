@@ -0,0 +1,305 @@
+use crate::report::{differing_cells, SolveReport};
+use crate::svg::{self, SvgOptions};
+use std::fmt::Write;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; }
+th, td { padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; text-align: left; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.ok { color: #080; }
+.bad { color: #c00; }
+section { margin-top: 3em; padding-top: 1em; border-top: 2px solid #222; }
+nav { margin: 0.5em 0; }
+nav a { margin-right: 1em; }
+.boards { display: flex; gap: 2em; flex-wrap: wrap; }
+.boards figure { margin: 0; }
+.boards figcaption { font-weight: bold; margin-bottom: 0.5em; }
+ol.words { columns: 3; }
+";
+
+/// Escape text for use inside html
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Short name for a report, used in headings and links
+fn name(report: &SolveReport, index: usize) -> String {
+    let base = match report.puzzle.id {
+        Some(id) => format!("Puzzle {}", id),
+        None => format!("Board {}", index + 1),
+    };
+    match &report.puzzle.title {
+        Some(title) => format!("{}: {}", base, title),
+        None => base,
+    }
+}
+
+/// Anchor for a report's section
+fn anchor(index: usize) -> String {
+    format!("p{}", index + 1)
+}
+
+fn status(report: &SolveReport) -> (&'static str, &'static str) {
+    if report.timed_out {
+        ("bad", "timed out")
+    } else if !report.solved {
+        ("bad", "no solution")
+    } else {
+        match report.matches_official() {
+            Some(true) => ("ok", "matches official"),
+            Some(false) => ("bad", "differs from official"),
+            None => ("ok", "solved"),
+        }
+    }
+}
+
+/// Build a single, self contained, html page describing a batch of solves
+///
+/// Everything, including the board images, is inlined so the page can be
+/// archived on its own.
+pub fn render(title: &str, reports: &[SolveReport]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>", escape(title));
+    let _ = writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(out, "<h1 id=\"top\">{}</h1>", escape(title));
+
+    write_summary(&mut out, reports);
+    for (i, report) in reports.iter().enumerate() {
+        write_section(&mut out, reports, i, report);
+    }
+
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+fn write_summary(out: &mut String, reports: &[SolveReport]) {
+    let solved = reports.iter().filter(|r| r.solved).count();
+    let matching = reports
+        .iter()
+        .filter(|r| r.matches_official() == Some(true))
+        .count();
+    let total_ms: f64 = reports.iter().map(|r| r.elapsed_ms).sum();
+    let total_nodes: u64 = reports.iter().map(|r| r.stats.nodes).sum();
+    let _ = writeln!(
+        out,
+        "<p>{} boards, {} solved, {} matching the official solution, {:.1} ms and {} nodes in total.</p>",
+        reports.len(),
+        solved,
+        matching,
+        total_ms,
+        total_nodes
+    );
+
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Board</th><th>Result</th><th>Words</th><th>Time (ms)</th><th>Nodes</th><th>Backtracks</th></tr>"
+    );
+    for (i, report) in reports.iter().enumerate() {
+        let (class, text) = status(report);
+        let _ = writeln!(
+            out,
+            "<tr><td><a href=\"#{}\">{}</a></td><td class=\"{}\">{}</td><td class=\"num\">{}</td>\
+             <td class=\"num\">{:.1}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            anchor(i),
+            escape(&name(report, i)),
+            class,
+            text,
+            report.words.len(),
+            report.elapsed_ms,
+            report.stats.nodes,
+            report.stats.backtracks
+        );
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+fn write_section(out: &mut String, reports: &[SolveReport], i: usize, report: &SolveReport) {
+    let _ = writeln!(out, "<section id=\"{}\">", anchor(i));
+    let _ = writeln!(out, "<h2>{}</h2>", escape(&name(report, i)));
+
+    let _ = write!(out, "<nav>");
+    if i > 0 {
+        let _ = write!(
+            out,
+            "<a href=\"#{}\">&larr; {}</a>",
+            anchor(i - 1),
+            escape(&name(&reports[i - 1], i - 1))
+        );
+    }
+    let _ = write!(out, "<a href=\"#top\">Summary</a>");
+    if i + 1 < reports.len() {
+        let _ = write!(
+            out,
+            "<a href=\"#{}\">{} &rarr;</a>",
+            anchor(i + 1),
+            escape(&name(&reports[i + 1], i + 1))
+        );
+    }
+    let _ = writeln!(out, "</nav>");
+
+    let (class, text) = status(report);
+    let _ = writeln!(out, "<p class=\"{}\">{}</p>", class, text);
+    let _ = writeln!(out, "<ul>");
+    for (label, value) in [
+        ("Date", &report.puzzle.date),
+        ("Author", &report.puzzle.author),
+    ] {
+        if let Some(value) = value {
+            let _ = writeln!(out, "<li>{}: {}</li>", label, escape(value));
+        }
+    }
+    let _ = writeln!(
+        out,
+        "<li>Solve time: {:.2} ms</li>\n<li>Nodes: {}</li>\n<li>Backtracks: {}</li>",
+        report.elapsed_ms, report.stats.nodes, report.stats.backtracks
+    );
+    if let Some(difficulty) = &report.difficulty {
+        let _ = writeln!(
+            out,
+            "<li>Difficulty: {:.1}, {}</li>",
+            difficulty.score, difficulty.band
+        );
+    }
+    let _ = writeln!(out, "</ul>");
+
+    let diff = match &report.official {
+        Some(official) if report.solved => differing_cells(&report.words, official),
+        _ => Vec::new(),
+    };
+    let options = SvgOptions {
+        numbers: true,
+        highlight: diff.clone(),
+        ..SvgOptions::default()
+    };
+
+    let _ = writeln!(out, "<div class=\"boards\">");
+    match report.board() {
+        Ok(board) => {
+            let caption = if report.solved {
+                "Found solution"
+            } else {
+                "Board"
+            };
+            let _ = writeln!(
+                out,
+                "<figure><figcaption>{}</figcaption>\n{}</figure>",
+                caption,
+                svg::render(&board, &options)
+            );
+        }
+        Err(e) => {
+            let _ = writeln!(
+                out,
+                "<p class=\"bad\">Couldn't draw board: {}</p>",
+                escape(&e.to_string())
+            );
+        }
+    }
+    if let Some(Ok(board)) = report.official_board() {
+        let _ = writeln!(
+            out,
+            "<figure><figcaption>Official solution</figcaption>\n{}</figure>",
+            svg::render(&board, &options)
+        );
+    }
+    let _ = writeln!(out, "</div>");
+
+    if !diff.is_empty() {
+        let cells: Vec<String> = diff.iter().map(|p| p.to_string()).collect();
+        let _ = writeln!(
+            out,
+            "<p class=\"bad\">{} cells differ, marked with dashed outlines: {}</p>",
+            diff.len(),
+            cells.join(", ")
+        );
+    }
+
+    if !report.words.is_empty() {
+        let _ = writeln!(out, "<ol class=\"words\">");
+        for word in &report.words {
+            let _ = writeln!(out, "<li>{}</li>", escape(&word.word));
+        }
+        let _ = writeln!(out, "</ol>");
+    }
+    let _ = writeln!(out, "</section>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::PuzzleInfo;
+    use crate::testing::{row_puzzle, GRID};
+    use crate::{Board, Point};
+    use std::time::Duration;
+
+    #[test]
+    fn page_links_every_board() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let solved = board.solve();
+        let info = PuzzleInfo {
+            title: Some("<Rows>".to_string()),
+            ..PuzzleInfo::default()
+        };
+        let report = SolveReport::new(info, None, &board, solved, Duration::from_millis(3));
+        let page = render("Run", &[report.clone(), report]);
+
+        assert!(page.contains("href=\"#p1\""));
+        assert!(page.contains("href=\"#p2\""));
+        assert!(page.contains("id=\"p2\""));
+        assert!(page.contains("&lt;Rows&gt;"));
+        assert_eq!(page.matches("<svg").count(), 2);
+        // No external assets
+        assert!(!page.contains("src="));
+        assert!(!page.contains("<link"));
+    }
+
+    #[test]
+    fn differing_cells_are_highlighted() {
+        // The official solution is each row as one word, which the solver
+        // doesn't find: it starts with FLAT rather than FLATCAR
+        let puzzle = row_puzzle();
+        let mut board = Board::try_from(puzzle.clone()).unwrap();
+        assert!(board.solve());
+        let report = SolveReport::new(
+            PuzzleInfo::default(),
+            Some(&puzzle),
+            &board,
+            true,
+            Duration::from_millis(3),
+        );
+        let diff = differing_cells(&report.words, report.official.as_ref().unwrap());
+        assert!(diff.contains(&Point::new(0, 0)));
+        // A word found on the same cells as an official one isn't marked
+        let same = report
+            .words
+            .iter()
+            .find(|w| w.points.length() == 7 && w.points.points()[0].x == 0)
+            .unwrap();
+        assert!(same.points.points().iter().all(|p| !diff.contains(p)));
+
+        let page = render("Run", &[report]);
+        // Each cell is outlined on both the found and the official board
+        assert_eq!(page.matches("stroke-dasharray").count(), 2 * diff.len());
+        let cells: Vec<String> = diff.iter().map(|p| p.to_string()).collect();
+        assert!(page.contains(&format!(
+            "{} cells differ, marked with dashed outlines: {}",
+            diff.len(),
+            cells.join(", ")
+        )));
+    }
+}
@@ -0,0 +1,123 @@
+//! A solver for the word game cell-tower over on andrewt.net
+//!
+//! A board is a grid of 12 rows of 7 letters, to be tiled with dictionary
+//! words. Each word is a connected group of cells, read in reading order, and
+//! each word must start at the first cell in reading order not yet covered.
+//!
+//! ```
+//! use cell_tower::{render, solve, Board, SolveOptions, Style};
+//!
+//! let board = Board::try_from(
+//!     "flatcar\nsnippet\nrustles\ncondign\nlousier\nspinner\n\
+//!      ponchos\nsummary\nsixties\nbogging\nspoofed\nalimony\n",
+//! )
+//! .unwrap();
+//! let solved = solve(&board, &SolveOptions::default()).unwrap().unwrap();
+//! assert_eq!(solved.words()[0].word, "flat");
+//! print!("{}", render(&solved, Style::Ascii));
+//! ```
+//!
+//! # Stability
+//!
+//! Everything re-exported here, at the top of the crate, is stable: it only
+//! changes along with the major version. That covers
+//!
+//! - building boards, with `Board::try_from` on a letter grid or
+//!   `Board::try_from` on a `Puzzle`, and choosing their `Dictionary` and
+//!   `Constraints`
+//! - searching, with `solve`, `enumerate` and `solutions` and their
+//!   `SolveOptions`
+//! - reading back a board, with `Board::words`, `PlacedWord`, `Board::rows`,
+//!   `Board::is_done` and `Board::stats`, and placing words by hand with
+//!   `Board::next_words`, `Board::insert_word` and `Board::remove_word`
+//! - drawing a board, with `render`
+//!
+//! Other public methods of these types are used by the command line tool and
+//! may change in any release. So may `run_cli`, which exists only so the
+//! binary can run it.
+//!
+//! # Features
+//!
+//! The command line tool is behind the `cli` feature, which is on by default
+//! and brings in clap, crossterm and isahc. Turn off default features to use
+//! the library without them:
+//!
+//! ```toml
+//! cell_tower = { version = "0.1", default-features = false }
+//! ```
+
+extern crate smallvec;
+extern crate colored;
+#[cfg(feature = "cli")]
+extern crate isahc;
+extern crate serde;
+extern crate serde_json;
+
+// The modules behind the cli feature only serve the command line tool
+#[cfg(feature = "cli")]
+mod bench;
+mod bitset;
+mod board;
+#[cfg(feature = "cli")]
+mod cache;
+#[cfg(feature = "cli")]
+mod cli;
+#[cfg(feature = "cli")]
+mod client;
+#[cfg(feature = "cli")]
+mod cnf;
+mod constraint;
+#[cfg(feature = "cli")]
+mod coverage;
+#[cfg(feature = "cli")]
+mod deduce;
+#[cfg(feature = "cli")]
+mod diff;
+mod dictionary;
+#[cfg(feature = "cli")]
+mod difficulty;
+mod dlx;
+mod frame;
+#[cfg(feature = "cli")]
+mod frequency;
+#[cfg(feature = "cli")]
+mod generate;
+#[cfg(feature = "cli")]
+mod given;
+#[cfg(feature = "cli")]
+mod hint;
+#[cfg(feature = "cli")]
+mod html;
+#[cfg(feature = "cli")]
+mod play;
+mod point;
+mod render;
+#[cfg(feature = "cli")]
+mod report;
+mod solve;
+#[cfg(feature = "cli")]
+mod svg;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "cli")]
+mod trace;
+#[cfg(feature = "cli")]
+mod traceable;
+mod web;
+
+use bitset::Bitset;
+
+pub use board::{Board, ParseError, PlacedWord, PuzzleError, SearchStats, TimedOut};
+pub use constraint::{Banned, Constraint, Constraints, Edge, Required, Shape};
+pub use dictionary::{Dictionary, LoadError};
+pub use dlx::Engine;
+pub use point::{Point, PointSet};
+pub use render::{render, Style};
+pub use solve::{enumerate, solutions, solve, SolveOptions};
+pub use web::Puzzle;
+
+/// Run the command line tool on the process's arguments, as the binary does
+#[cfg(feature = "cli")]
+pub fn run_cli() -> std::process::ExitCode {
+    cli::main()
+}
@@ -0,0 +1,475 @@
+use crate::board::TimedOut;
+use crate::hint::{Hints, Level};
+use crate::render::{self, Style};
+use crate::{Board, Point, PointSet};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Stylize;
+use crossterm::{cursor, execute, terminal};
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// A direction to move the cursor in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Why a selection couldn't be placed as the next word
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    /// No cells are selected
+    Empty,
+    /// Fewer cells are selected than a word may have
+    TooShort { len: usize, min: usize },
+    /// More cells are selected than a word may have
+    TooLong { len: usize, max: usize },
+    /// A selected cell already belongs to a word
+    Taken(Point),
+    /// The word doesn't cover the first free cell in reading order
+    NotAtRoot(Point),
+    /// The selected cells don't touch each other
+    NotContiguous,
+    /// The letters don't spell a word in the dictionary
+    NotAWord(String),
+    /// The word can't be read in order along the rows
+    Unreachable,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Empty => write!(f, "no cells selected"),
+            Rejection::TooShort { len, min } => {
+                write!(f, "words have at least {} letters, not {}", min, len)
+            }
+            Rejection::TooLong { len, max } => {
+                write!(f, "words have at most {} letters, not {}", max, len)
+            }
+            Rejection::Taken(p) => write!(f, "{} is already part of a word", p),
+            Rejection::NotAtRoot(p) => write!(f, "the next word must start at {}", p),
+            Rejection::NotContiguous => write!(f, "the cells must all be connected"),
+            Rejection::NotAWord(word) => write!(f, "{} isn't in the dictionary", word),
+            Rejection::Unreachable => write!(f, "the word can't be read in order"),
+        }
+    }
+}
+
+/// The state of a game, kept apart from the terminal so it can be tested
+pub struct Game {
+    board: Board,
+    cursor: Point,
+    /// Selected cells, in the order they were picked
+    selection: Vec<Point>,
+    /// Words placed so far, oldest first
+    placed: Vec<PointSet>,
+    /// Words taken back with undo, most recent last
+    undone: Vec<PointSet>,
+    hints: Hints,
+}
+
+impl Game {
+    /// Start a game on the given board, clearing any words already placed
+    pub fn new(mut board: Board) -> Game {
+        board.reset();
+        Game {
+            board,
+            cursor: Point::new(0, 0),
+            selection: Vec::new(),
+            placed: Vec::new(),
+            undone: Vec::new(),
+            hints: Hints::new(),
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn cursor(&self) -> Point {
+        self.cursor
+    }
+
+    pub fn selection(&self) -> &[Point] {
+        &self.selection
+    }
+
+    /// Move the cursor one cell, stopping at the edges of the board
+    pub fn move_cursor(&mut self, direction: Direction) {
+        let next = match direction {
+            Direction::Up => self.cursor.above(),
+            Direction::Down => self.cursor.below(),
+            Direction::Left => self.cursor.left(),
+            Direction::Right => self.cursor.right(),
+        };
+        if let Some(next) = next {
+            self.cursor = next;
+        }
+    }
+
+    /// Select the cell under the cursor, or deselect it if already selected
+    pub fn toggle(&mut self) {
+        match self.selection.iter().position(|&p| p == self.cursor) {
+            Some(i) => {
+                self.selection.remove(i);
+            }
+            None => self.selection.push(self.cursor),
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    /// The letters of the current selection, in reading order
+    pub fn selected_word(&self) -> String {
+        let points = PointSet::from(self.selection.as_slice()).sorted();
+        points
+            .points()
+            .iter()
+            .map(|&p| self.board[p] as char)
+            .collect()
+    }
+
+    /// Check the selection is a legal next word, returning the cells in
+    /// reading order
+    pub fn check_selection(&self) -> Result<PointSet, Rejection> {
+        if self.selection.is_empty() {
+            return Err(Rejection::Empty);
+        }
+        let len = self.selection.len();
+        let (min, max) = self.board.word_sizes();
+        if len > max {
+            return Err(Rejection::TooLong { len, max });
+        }
+        if let Some(&p) = self
+            .selection
+            .iter()
+            .find(|&&p| self.board.word_id(p).is_some())
+        {
+            return Err(Rejection::Taken(p));
+        }
+        let points = PointSet::from(self.selection.as_slice()).sorted();
+        // Every cell before the root is taken, so the root must come first
+        if let Some(root) = self.board.get_root() {
+            if points.points()[0] != root {
+                return Err(Rejection::NotAtRoot(root));
+            }
+        }
+        if !points.contiguous() {
+            return Err(Rejection::NotContiguous);
+        }
+        if len < min {
+            return Err(Rejection::TooShort { len, min });
+        }
+        let word = self.selected_word();
+        if !self.board.dictionary().is_word(word.as_bytes()) {
+            return Err(Rejection::NotAWord(word));
+        }
+        // The solver's own list of moves has the last say
+        if !self.board.next_words().contains(&points) {
+            return Err(Rejection::Unreachable);
+        }
+        Ok(points)
+    }
+
+    /// Place the selection as the next word, if it is legal
+    pub fn submit(&mut self) -> Result<String, Rejection> {
+        let points = self.check_selection()?;
+        let word = self.selected_word();
+        self.board.insert_word(&points);
+        self.placed.push(points);
+        self.undone.clear();
+        self.selection.clear();
+        self.hints.reset();
+        Ok(word)
+    }
+
+    /// Take back the last word placed, returning false if there was none
+    pub fn undo(&mut self) -> bool {
+        match self.placed.pop() {
+            Some(points) => {
+                self.board.remove_word(&points);
+                self.undone.push(points);
+                self.hints.reset();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Place the last word taken back, returning false if there was none
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(points) => {
+                self.board.insert_word(&points);
+                self.placed.push(points);
+                self.selection.clear();
+                self.hints.reset();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.board.is_done()
+    }
+
+    /// Give away a little more about the next word, following on from the
+    /// words placed so far
+    pub fn hint(&mut self, deadline: Option<Instant>) -> Result<String, TimedOut> {
+        Ok(match self.hints.next(&self.board, deadline)? {
+            Some((hint, level)) => hint.describe(level),
+            None if self.board.is_done() => "The board is already done".to_string(),
+            None => "No solution follows from these words, try undoing some".to_string(),
+        })
+    }
+
+    /// Cells to point out, once a hint has given them away
+    pub fn hinted_cells(&self) -> &[Point] {
+        match self.hints.current() {
+            Some((hint, level)) if level >= Level::Cells => hint.points.points(),
+            _ => &[],
+        }
+    }
+
+    pub fn n_placed(&self) -> usize {
+        self.placed.len()
+    }
+}
+
+const HELP: &str =
+    "arrows/hjkl move, space select, enter place word, esc clear, ? hint, u undo, r redo, q quit";
+
+/// Puts the terminal back how it was, even if drawing fails part way
+pub(crate) struct RawMode;
+
+impl RawMode {
+    pub(crate) fn enter() -> io::Result<RawMode> {
+        terminal::enable_raw_mode()?;
+        let guard = RawMode;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn draw(game: &Game, style: Style, status: &str) -> io::Result<()> {
+    let text = render::render_marked(game.board(), style, &|point, text| {
+        if point == game.cursor() {
+            text.reverse().to_string()
+        } else if game.selection().contains(&point) {
+            text.black().on_yellow().to_string()
+        } else if game.hinted_cells().contains(&point) {
+            text.underlined().to_string()
+        } else if game.board().word_id(point).is_some() {
+            text.dim().to_string()
+        } else {
+            text
+        }
+    });
+    let mut out = io::stdout().lock();
+    execute!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    // Raw mode doesn't turn newlines into carriage returns
+    for line in text.lines() {
+        write!(out, "{}\r\n", line)?;
+    }
+    write!(out, "\r\n")?;
+    if game.is_done() {
+        write!(
+            out,
+            "Solved with {} words! Press q to quit\r\n",
+            game.n_placed()
+        )?;
+    } else {
+        if let Some(root) = game.board().get_root() {
+            write!(out, "Next word starts at {}", root)?;
+        }
+        if !game.selection().is_empty() {
+            write!(out, ", selected: {}", game.selected_word().to_uppercase())?;
+        }
+        write!(out, "\r\n")?;
+    }
+    write!(out, "{}\r\n{}\r\n", status, HELP)?;
+    out.flush()
+}
+
+/// Play the game in the terminal until the user quits
+///
+/// Returns true if the board was completed. Searches for hints give up after
+/// `timeout`, if one is given.
+pub fn run(game: &mut Game, style: Style, timeout: Option<Duration>) -> io::Result<bool> {
+    let _raw = RawMode::enter()?;
+    let mut status = String::new();
+    loop {
+        draw(game, style, &status)?;
+        let key = match event::read()? {
+            Event::Key(
+                key @ KeyEvent {
+                    kind: KeyEventKind::Press,
+                    ..
+                },
+            ) => key,
+            _ => continue,
+        };
+        status.clear();
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('q') => break,
+            KeyCode::Up | KeyCode::Char('k') => game.move_cursor(Direction::Up),
+            KeyCode::Down | KeyCode::Char('j') => game.move_cursor(Direction::Down),
+            KeyCode::Left | KeyCode::Char('h') => game.move_cursor(Direction::Left),
+            KeyCode::Right | KeyCode::Char('l') => game.move_cursor(Direction::Right),
+            KeyCode::Char(' ') => game.toggle(),
+            KeyCode::Esc | KeyCode::Backspace => game.clear_selection(),
+            KeyCode::Enter => {
+                status = match game.submit() {
+                    Ok(word) => format!("Placed {}", word.to_uppercase()),
+                    Err(e) => format!("Can't place that: {}", e),
+                }
+            }
+            KeyCode::Char('?') => {
+                status = match game.hint(timeout.map(|t| Instant::now() + t)) {
+                    Ok(hint) => hint,
+                    Err(e) => format!("No hint: {}", e),
+                }
+            }
+            KeyCode::Char('u') if !game.undo() => status = "Nothing to undo".to_string(),
+            KeyCode::Char('r') if !game.redo() => status = "Nothing to redo".to_string(),
+            _ => {}
+        }
+    }
+    Ok(game.is_done())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{row_puzzle, GRID};
+    use crate::Puzzle;
+
+    fn select(game: &mut Game, cells: &[(u8, u8)]) {
+        game.clear_selection();
+        for &(x, y) in cells {
+            game.cursor = Point::new(x, y);
+            game.toggle();
+        }
+    }
+
+    #[test]
+    fn places_legal_words_only() {
+        let mut game = Game::new(Board::try_from(GRID).unwrap());
+        select(&mut game, &[(1, 0), (2, 0), (3, 0)]);
+        assert_eq!(game.submit(), Err(Rejection::NotAtRoot(Point::new(0, 0))));
+        select(&mut game, &[(0, 0), (1, 0), (3, 0)]);
+        assert_eq!(game.submit(), Err(Rejection::NotContiguous));
+        select(&mut game, &[(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(game.submit(), Err(Rejection::TooShort { len: 3, min: 4 }));
+        select(&mut game, &[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]);
+        assert_eq!(game.submit(), Err(Rejection::NotAWord("flatc".to_string())));
+
+        // Picking order doesn't matter, words are read in reading order
+        select(&mut game, &[(3, 0), (0, 0), (2, 0), (1, 0)]);
+        assert_eq!(game.submit(), Ok("flat".to_string()));
+        assert_eq!(game.board().word_id(Point::new(3, 0)), Some(0));
+        assert!(game.selection().is_empty());
+
+        select(&mut game, &[(0, 0)]);
+        assert_eq!(game.submit(), Err(Rejection::Taken(Point::new(0, 0))));
+    }
+
+    #[test]
+    fn word_sizes_come_from_the_puzzle() {
+        let board = Board::try_from(Puzzle {
+            min_size: 5,
+            max_size: 6,
+            ..row_puzzle()
+        })
+        .unwrap();
+        let mut game = Game::new(board);
+        select(&mut game, &[(0, 0), (1, 0), (2, 0), (3, 0)]);
+        let short = game.submit().unwrap_err();
+        assert_eq!(short, Rejection::TooShort { len: 4, min: 5 });
+        assert_eq!(short.to_string(), "words have at least 5 letters, not 4");
+        select(
+            &mut game,
+            &[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0), (6, 0)],
+        );
+        let long = game.submit().unwrap_err();
+        assert_eq!(long, Rejection::TooLong { len: 7, max: 6 });
+        assert_eq!(long.to_string(), "words have at most 6 letters, not 7");
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut game = Game::new(Board::try_from(GRID).unwrap());
+        assert!(!game.undo());
+        select(&mut game, &[(0, 0), (1, 0), (2, 0), (3, 0)]);
+        game.submit().unwrap();
+        assert!(game.undo());
+        assert_eq!(game.board().get_root(), Some(Point::new(0, 0)));
+        assert!(game.redo());
+        assert_eq!(game.board().get_root(), Some(Point::new(4, 0)));
+        assert!(!game.redo());
+    }
+
+    #[test]
+    fn hints_start_over_after_each_word() {
+        let mut game = Game::new(Board::try_from(GRID).unwrap());
+        assert!(game.hint(None).unwrap().contains("(0, 0)"));
+        assert!(game.hinted_cells().is_empty());
+        game.hint(None).unwrap();
+        game.hint(None).unwrap();
+        let cells = game.hinted_cells().to_vec();
+        assert!(!cells.is_empty());
+
+        select(&mut game, &[(0, 0), (1, 0), (2, 0), (3, 0)]);
+        game.submit().unwrap();
+        assert!(game.hinted_cells().is_empty());
+        assert!(game
+            .hint(None)
+            .unwrap()
+            .starts_with("The next word starts at"));
+    }
+
+    #[test]
+    fn completes_with_a_solution() {
+        let mut solved = Board::try_from(GRID).unwrap();
+        assert!(solved.solve());
+        let mut game = Game::new(solved.clone());
+        for word in solved.words() {
+            assert!(!game.is_done());
+            let cells: Vec<(u8, u8)> = word.points.points().iter().map(|p| (p.x, p.y)).collect();
+            select(&mut game, &cells);
+            game.submit().unwrap();
+        }
+        assert!(game.is_done());
+    }
+
+    #[test]
+    fn cursor_stays_on_board() {
+        let mut game = Game::new(Board::try_from(GRID).unwrap());
+        game.move_cursor(Direction::Up);
+        game.move_cursor(Direction::Left);
+        assert_eq!(game.cursor(), Point::new(0, 0));
+        for _ in 0..20 {
+            game.move_cursor(Direction::Right);
+            game.move_cursor(Direction::Down);
+        }
+        assert_eq!(game.cursor(), Point::new(6, 11));
+    }
+}
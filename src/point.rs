@@ -1,5 +1,7 @@
 use smallvec::SmallVec;
 use crate::Bitset;
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
 use std::fmt;
 
 #[derive(Copy,Clone,PartialEq,Eq)]
@@ -73,7 +75,7 @@ impl fmt::Debug for Point {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone,PartialEq,Eq)]
 pub struct PointSet (SmallVec<[Point; 8]>);
 
 impl PointSet {
@@ -84,6 +86,11 @@ impl PointSet {
         PointSet(points)
     }
 
+    /// Construct a PointSet with no points
+    pub fn empty() -> PointSet {
+        PointSet(SmallVec::new())
+    }
+
     pub fn push(&mut self, point: Point) {
         self.0.push(point)
     }
@@ -92,6 +99,18 @@ impl PointSet {
         self.0.len()
     }
 
+    /// Return the points, in the order they were added
+    pub fn points(&self) -> &[Point] {
+        &self.0
+    }
+
+    /// Return a copy with the points sorted into reading order
+    pub fn sorted(&self) -> PointSet {
+        let mut points = self.0.clone();
+        points.sort_by_key(|p| (p.y, p.x));
+        PointSet(points)
+    }
+
     /// Return a bitset, where each valid point is marked with a true
     fn make_bitset(&self) -> Bitset {
         Bitset::first_n(self.length())
@@ -106,7 +125,7 @@ impl PointSet {
     fn mark_index(&self, index: usize, visited: &mut Bitset) {
         if !visited.get(index) {
             visited.set(index, true);
-            let point = self.0[index as usize];
+            let point = self.0[index];
 
             if let Some(neighbor) = point.above() {
                 if let Some(n_index) = self.index_of(neighbor) {
@@ -144,7 +163,7 @@ impl PointSet {
 
     /// Mark all points on the bottom edge
     /// Defined as:
-    /// ```
+    /// ```text
     /// . . s x x x x x
     /// x x x
     /// ```
@@ -173,7 +192,7 @@ impl PointSet {
     /// When this returns true, there may be a way to conenct the points
     ///
     /// Key: . -> unselected, s -> selected, x -> eligible for connections
-    /// ```
+    /// ```text
     /// . . s . . . s .
     /// s s s s x x x x    Connectable
     /// x x x x
@@ -215,6 +234,42 @@ impl From<&[Point]> for PointSet {
     }
 }
 
+/// Points are written as `[x, y]`, matching the puzzle files
+impl Serialize for Point {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        [self.x, self.y].serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Point {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Point, D::Error> {
+        let [x, y] = <[u8; 2]>::deserialize(deserializer)?;
+        if x > 6 || y > 11 {
+            return Err(serde::de::Error::custom(format!(
+                "point ({}, {}) is off the board",
+                x, y
+            )));
+        }
+        Ok(Point::new(x, y))
+    }
+}
+
+impl Serialize for PointSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.as_slice().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PointSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<PointSet, D::Error> {
+        let points = Vec::<Point>::deserialize(deserializer)?;
+        if points.len() > 8 {
+            return Err(serde::de::Error::custom("a word covers at most 8 points"));
+        }
+        Ok(points.as_slice().into())
+    }
+}
+
 impl fmt::Display for PointSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self.0.as_slice())
@@ -0,0 +1,175 @@
+use crate::{Board, Point};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+
+/// Ways of drawing a board as text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum Style {
+    /// Reverse video joins the letters of each word, needs a colour terminal
+    Color,
+    /// Unicode box drawing lines around each word
+    Box,
+    /// Like box, but only using `+`, `-` and `|`
+    Ascii,
+}
+
+/// Characters used to draw borders
+struct Glyphs {
+    horizontal: char,
+    vertical: char,
+    /// Indexed by which arms leave the corner: up, down, left, right
+    corners: [char; 16],
+}
+
+const UP: usize = 1;
+const DOWN: usize = 2;
+const LEFT: usize = 4;
+const RIGHT: usize = 8;
+
+const BOX: Glyphs = Glyphs {
+    horizontal: '─',
+    vertical: '│',
+    corners: [
+        ' ', '╵', '╷', '│', '╴', '┘', '┐', '┤', '╶', '└', '┌', '├', '─', '┴', '┬', '┼',
+    ],
+};
+
+const ASCII: Glyphs = Glyphs {
+    horizontal: '-',
+    vertical: '|',
+    corners: [
+        ' ', '|', '|', '|', '-', '+', '+', '+', '-', '+', '+', '+', '-', '+', '+', '+',
+    ],
+};
+
+/// Test if a border runs down the left side of cell (x, y)
+///
+/// `x` may be 7, for the right hand edge of the board.
+fn has_vertical(board: &Board, x: usize, y: usize) -> bool {
+    x == 0 || x == 7 || !board.is_h_connection(x - 1, y)
+}
+
+/// Test if a border runs along the top of cell (x, y)
+///
+/// `y` may be 12, for the bottom edge of the board.
+fn has_horizontal(board: &Board, x: usize, y: usize) -> bool {
+    y == 0 || y == 12 || !board.is_v_connection(x, y - 1)
+}
+
+/// Pick the glyph for the corner at the top left of cell (x, y)
+fn corner(board: &Board, glyphs: &Glyphs, x: usize, y: usize) -> char {
+    if (1..7).contains(&x) && (1..12).contains(&y) && board.is_d_connection(x - 1, y - 1) {
+        // All four cells are the same word, so nothing meets here
+        return ' ';
+    }
+    let mut arms = 0;
+    if y > 0 && has_vertical(board, x, y - 1) {
+        arms |= UP;
+    }
+    if y < 12 && has_vertical(board, x, y) {
+        arms |= DOWN;
+    }
+    if x > 0 && has_horizontal(board, x - 1, y) {
+        arms |= LEFT;
+    }
+    if x < 7 && has_horizontal(board, x, y) {
+        arms |= RIGHT;
+    }
+    glyphs.corners[arms]
+}
+
+/// Draw the board with lines around each word, without using colour
+///
+/// The text of each cell is passed through `cell`, so callers can mark it up.
+fn render_lines(board: &Board, glyphs: &Glyphs, cell: &dyn Fn(Point, String) -> String) -> String {
+    let mut out = String::new();
+    for y in 0..=12 {
+        // Border line above row y
+        for x in 0..7 {
+            out.push(corner(board, glyphs, x, y));
+            let c = if has_horizontal(board, x, y) {
+                glyphs.horizontal
+            } else {
+                ' '
+            };
+            out.extend([c, c, c]);
+        }
+        out.push(corner(board, glyphs, 7, y));
+        out.push('\n');
+        if y == 12 {
+            break;
+        }
+
+        // Letters of row y
+        for x in 0..=7 {
+            out.push(if has_vertical(board, x, y) {
+                glyphs.vertical
+            } else {
+                ' '
+            });
+            if x < 7 {
+                let point = Point::new(x as u8, y as u8);
+                let letter = board[point].to_ascii_uppercase() as char;
+                out.push_str(&cell(point, format!(" {} ", letter)));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Draw the board as text in the given style
+pub fn render(board: &Board, style: Style) -> String {
+    match style {
+        Style::Color => board.to_string(),
+        Style::Box => render_lines(board, &BOX, &|_, text| text),
+        Style::Ascii => render_lines(board, &ASCII, &|_, text| text),
+    }
+}
+
+/// Draw the board with lines around each word, letting the caller decorate
+/// each cell's text, such as to show a cursor
+///
+/// The colour style has no lines of its own, so is drawn as box.
+#[cfg(feature = "cli")]
+pub fn render_marked(
+    board: &Board,
+    style: Style,
+    cell: &dyn Fn(Point, String) -> String,
+) -> String {
+    match style {
+        Style::Color | Style::Box => render_lines(board, &BOX, cell),
+        Style::Ascii => render_lines(board, &ASCII, cell),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+
+    #[test]
+    fn box_outline_of_solution() {
+        let mut board = Board::try_from(GRID).unwrap();
+        assert!(board.solve());
+        let text = render(&board, Style::Box);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 25);
+        // flat + carpet, where carpet wraps onto the second row
+        assert_eq!(lines[0], "┌───────────────┬───────────┐");
+        assert_eq!(lines[1], "│ F   L   A   T │ C   A   R │");
+        assert_eq!(lines[2], "├───────────────┤           │");
+        assert_eq!(lines[3], "│ S   N   I   P │ P   E   T │");
+        assert_eq!(lines[4], "├───────────────┴───────────┤");
+        assert_eq!(lines[24], "└───────────────────────────┘");
+    }
+
+    #[test]
+    fn ascii_only_uses_ascii() {
+        let board = Board::try_from(GRID).unwrap();
+        let text = render(&board, Style::Ascii);
+        assert!(text.is_ascii());
+        assert_eq!(text.lines().next(), Some("+---+---+---+---+---+---+---+"));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::board::{ParseError, PlacedWord, SearchStats};
+use crate::difficulty::Difficulty;
+use crate::{Board, Point, Puzzle};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Where a board came from, and anything the site told us about it
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PuzzleInfo {
+    pub id: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+}
+
+impl PuzzleInfo {
+    pub fn new(id: Option<usize>, puzzle: Option<&Puzzle>) -> PuzzleInfo {
+        PuzzleInfo {
+            id,
+            title: puzzle.and_then(|p| p.title()),
+            date: puzzle.and_then(|p| p.date()),
+            author: puzzle.and_then(|p| p.author()),
+        }
+    }
+}
+
+/// The result of solving a single board
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SolveReport {
+    pub puzzle: PuzzleInfo,
+    /// The letters of the board, one string per row
+    pub grid: Vec<String>,
+    pub solved: bool,
+    /// The search gave up before finishing
+    #[serde(default)]
+    pub timed_out: bool,
+    /// Wall clock time spent searching, in milliseconds
+    pub elapsed_ms: f64,
+    pub stats: SearchStats,
+    /// Words of the solution found, empty if there was none
+    pub words: Vec<PlacedWord>,
+    /// Words of the puzzle's own solution, when it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub official: Option<Vec<PlacedWord>>,
+    /// How hard the puzzle is for a person, when it was rated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<Difficulty>,
+}
+
+impl SolveReport {
+    pub fn new(
+        info: PuzzleInfo,
+        puzzle: Option<&Puzzle>,
+        board: &Board,
+        solved: bool,
+        elapsed: Duration,
+    ) -> SolveReport {
+        SolveReport {
+            puzzle: info,
+            grid: board.rows(),
+            solved,
+            timed_out: false,
+            elapsed_ms: millis(elapsed),
+            stats: board.stats(),
+            words: board.words(),
+            official: puzzle
+                .and_then(|p| Board::official_solution(p).ok())
+                .map(|b| b.words()),
+            difficulty: None,
+        }
+    }
+
+    /// Rebuild the board with the found solution placed on it
+    pub fn board(&self) -> Result<Board, ParseError> {
+        board_with_words(&self.grid, &self.words)
+    }
+
+    /// Rebuild the board with the official solution placed on it
+    pub fn official_board(&self) -> Option<Result<Board, ParseError>> {
+        self.official
+            .as_ref()
+            .map(|words| board_with_words(&self.grid, words))
+    }
+
+    /// Test if the found solution is the official one
+    pub fn matches_official(&self) -> Option<bool> {
+        self.official
+            .as_ref()
+            .map(|official| differing_cells(&self.words, official).is_empty())
+    }
+}
+
+/// Build a board from its rows of letters, then place the words in order
+pub fn board_with_words(grid: &[String], words: &[PlacedWord]) -> Result<Board, ParseError> {
+    let mut board = Board::try_from(grid.join("\n").as_str())?;
+    for word in words {
+        board.insert_word(&word.points);
+    }
+    Ok(board)
+}
+
+/// Return the cells whose word covers a different set of cells in each
+/// solution, in reading order
+pub fn differing_cells(a: &[PlacedWord], b: &[PlacedWord]) -> Vec<Point> {
+    let regions = |words: &[PlacedWord]| {
+        let mut cells = vec![None; 84];
+        for word in words {
+            let sorted = word.points.sorted();
+            for p in sorted.points() {
+                cells[p.y as usize * 7 + p.x as usize] = Some(sorted.clone());
+            }
+        }
+        cells
+    };
+    let (a, b) = (regions(a), regions(b));
+    (0..84)
+        .filter(|&i| a[i] != b[i])
+        .map(|i| Point::new((i % 7) as u8, (i / 7) as u8))
+        .collect()
+}
+
+/// One solution found while enumerating, written as a single json line
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SolutionLine {
+    pub puzzle: PuzzleInfo,
+    /// Position of this solution in the order found, starting at 1
+    pub index: usize,
+    /// Time from the start of the search until this solution was found
+    pub elapsed_ms: f64,
+    pub stats: SearchStats,
+    pub words: Vec<PlacedWord>,
+}
+
+pub fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,108 @@
+use crate::board::TimedOut;
+use crate::constraint::Constraints;
+use crate::dictionary::Dictionary;
+use crate::dlx::Engine;
+use crate::Board;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+/// Settings for `solve`, `enumerate` and `solutions`
+///
+/// The default searches by backtracking, with no time limit, using the
+/// board's own dictionary and constraints.
+#[derive(Clone, Default)]
+pub struct SolveOptions {
+    pub engine: Engine,
+    /// Give up once the search has run this long
+    pub timeout: Option<Duration>,
+    /// Words to tile the board with, instead of the board's own
+    pub dictionary: Option<Dictionary>,
+    /// Rules solutions must follow, instead of the board's own
+    pub constraints: Option<Constraints>,
+}
+
+impl SolveOptions {
+    /// A copy of the board to search, with these settings applied
+    fn prepare(&self, board: &Board) -> Board {
+        let mut board = board.clone();
+        if let Some(dict) = self.dictionary {
+            board.set_dictionary(dict);
+        }
+        if let Some(constraints) = &self.constraints {
+            board.set_constraints(constraints.clone());
+        }
+        board.reset_stats();
+        board
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.timeout.map(|timeout| Instant::now() + timeout)
+    }
+}
+
+/// Find a solution to the board, keeping any words already placed
+///
+/// Returns `None` if there is no solution. The solved board's `stats` count
+/// the work done.
+pub fn solve(board: &Board, options: &SolveOptions) -> Result<Option<Board>, TimedOut> {
+    let mut board = options.prepare(board);
+    let solved = options.engine.solve_until(&mut board, options.deadline())?;
+    Ok(if solved { Some(board) } else { None })
+}
+
+/// Call `found` with each solution to the board as it is found, stopping
+/// early if it returns `Break`
+pub fn enumerate(
+    board: &Board,
+    options: &SolveOptions,
+    mut found: impl FnMut(&Board) -> ControlFlow<()>,
+) -> Result<(), TimedOut> {
+    let mut board = options.prepare(board);
+    options
+        .engine
+        .enumerate_until(&mut board, options.deadline(), &mut found)
+}
+
+/// Return every solution to the board
+pub fn solutions(board: &Board, options: &SolveOptions) -> Result<Vec<Board>, TimedOut> {
+    let mut solutions = Vec::new();
+    enumerate(board, options, |solution| {
+        solutions.push(solution.clone());
+        ControlFlow::Continue(())
+    })?;
+    Ok(solutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Banned;
+    use crate::testing::GRID;
+
+    #[test]
+    fn options_are_applied() {
+        let board = Board::try_from(GRID).unwrap();
+        let all = solutions(&board, &SolveOptions::default()).unwrap();
+        let options = SolveOptions {
+            engine: Engine::Dlx,
+            constraints: Some(Constraints::new(vec![Box::new(Banned(
+                "spinners".to_string(),
+            ))])),
+            ..SolveOptions::default()
+        };
+        let some = solutions(&board, &options).unwrap();
+        assert!(!some.is_empty() && some.len() < all.len());
+
+        let solved = solve(&board, &options).unwrap().unwrap();
+        assert!(solved.is_done());
+        assert!(solved.words().iter().all(|w| w.word != "spinners"));
+        // The board passed in is left alone
+        assert_eq!(board.n_words(), 0);
+
+        let options = SolveOptions {
+            timeout: Some(Duration::ZERO),
+            ..SolveOptions::default()
+        };
+        assert_eq!(solve(&board, &options).err(), Some(TimedOut));
+    }
+}
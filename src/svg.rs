@@ -0,0 +1,234 @@
+use crate::{Board, Point};
+use std::fmt::Write;
+
+/// Settings for drawing a board as an svg image
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    /// Width and height of a single cell, in pixels
+    pub cell_size: u32,
+    /// Fill each word's region with its own colour
+    pub fill: bool,
+    /// Number each word in reading order
+    pub numbers: bool,
+    /// Cells to mark, such as where two solutions differ
+    pub highlight: Vec<Point>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions {
+            cell_size: 40,
+            fill: true,
+            numbers: false,
+            highlight: Vec::new(),
+        }
+    }
+}
+
+/// A light colour for the given word, spread around the colour wheel so
+/// neighbouring words are easy to tell apart
+pub fn region_color(word_id: usize) -> String {
+    let hue = (word_id as f64 * 137.508) % 360.0;
+    format!("hsl({:.0}, 70%, 82%)", hue)
+}
+
+/// Draw the board, with each word's region outlined
+///
+/// Region borders follow the same connection rules as the terminal
+/// display: a border is drawn between two cells unless `is_h_connection` or
+/// `is_v_connection` joins them, and a corner is drawn where four cells
+/// meet unless `is_d_connection` says they are all one word.
+pub fn render(board: &Board, options: &SvgOptions) -> String {
+    let s = options.cell_size as f64;
+    let margin = (s / 4.0).round();
+    let thick = (s / 14.0).max(1.5);
+    let width = 7.0 * s + 2.0 * margin;
+    let height = 12.0 * s + 2.0 * margin;
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect width="{}" height="{}" fill="white"/>"#,
+        width, height
+    );
+    let _ = writeln!(out, r#"<g transform="translate({m} {m})">"#, m = margin);
+
+    // Cell backgrounds
+    for y in 0..12 {
+        for x in 0..7 {
+            let fill = match board.word_id(Point::new(x as u8, y as u8)) {
+                Some(id) if options.fill => region_color(id),
+                _ => "white".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{s}" height="{s}" fill="{}"/>"#,
+                x as f64 * s,
+                y as f64 * s,
+                fill,
+                s = s
+            );
+        }
+    }
+
+    // Faint lines between every cell
+    let _ = writeln!(out, r##"<g stroke="#ccc" stroke-width="1">"##);
+    for x in 1..7 {
+        let _ = writeln!(
+            out,
+            r#"<line x1="{x}" y1="0" x2="{x}" y2="{}"/>"#,
+            12.0 * s,
+            x = x as f64 * s
+        );
+    }
+    for y in 1..12 {
+        let _ = writeln!(
+            out,
+            r#"<line x1="0" y1="{y}" x2="{}" y2="{y}"/>"#,
+            7.0 * s,
+            y = y as f64 * s
+        );
+    }
+    let _ = writeln!(out, "</g>");
+
+    // Region borders
+    let _ = writeln!(
+        out,
+        r##"<g stroke="#222" stroke-width="{}" stroke-linecap="square">"##,
+        thick
+    );
+    for y in 0..12 {
+        for x in 0..6 {
+            if !board.is_h_connection(x, y) {
+                let lx = (x + 1) as f64 * s;
+                let _ = writeln!(
+                    out,
+                    r#"<line x1="{lx}" y1="{}" x2="{lx}" y2="{}"/>"#,
+                    y as f64 * s,
+                    (y + 1) as f64 * s,
+                    lx = lx
+                );
+            }
+        }
+    }
+    for y in 0..11 {
+        for x in 0..7 {
+            if !board.is_v_connection(x, y) {
+                let ly = (y + 1) as f64 * s;
+                let _ = writeln!(
+                    out,
+                    r#"<line x1="{}" y1="{ly}" x2="{}" y2="{ly}"/>"#,
+                    x as f64 * s,
+                    (x + 1) as f64 * s,
+                    ly = ly
+                );
+            }
+        }
+    }
+    let _ = writeln!(out, "</g>");
+    // Square off the inside corners where borders meet
+    for y in 0..11 {
+        for x in 0..6 {
+            if !board.is_d_connection(x, y) {
+                let _ = writeln!(
+                    out,
+                    r##"<rect x="{}" y="{}" width="{t}" height="{t}" fill="#222"/>"##,
+                    (x + 1) as f64 * s - thick / 2.0,
+                    (y + 1) as f64 * s - thick / 2.0,
+                    t = thick
+                );
+            }
+        }
+    }
+    let _ = writeln!(
+        out,
+        r##"<rect width="{}" height="{}" fill="none" stroke="#222" stroke-width="{}"/>"##,
+        7.0 * s,
+        12.0 * s,
+        thick * 2.0
+    );
+
+    for p in &options.highlight {
+        let inset = thick * 1.5;
+        let _ = writeln!(
+            out,
+            r##"<rect x="{}" y="{}" width="{w}" height="{w}" fill="none" stroke="#d00" stroke-width="{}" stroke-dasharray="4 2"/>"##,
+            p.x as f64 * s + inset,
+            p.y as f64 * s + inset,
+            thick,
+            w = s - 2.0 * inset
+        );
+    }
+
+    // Letters, and word numbers in the corner of each word's first cell
+    let _ = writeln!(
+        out,
+        r#"<g font-family="sans-serif" text-anchor="middle" fill="black">"#
+    );
+    for y in 0..12 {
+        for x in 0..7 {
+            let letter = board[Point::new(x as u8, y as u8)].to_ascii_uppercase() as char;
+            let _ = writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-size="{}" dominant-baseline="central">{}</text>"#,
+                (x as f64 + 0.5) * s,
+                (y as f64 + 0.5) * s,
+                (s * 0.5).round(),
+                letter
+            );
+        }
+    }
+    if options.numbers {
+        for word in board.words() {
+            let first = word.points.points()[0];
+            let _ = writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-size="{}" text-anchor="start">{}</text>"#,
+                first.x as f64 * s + s * 0.08,
+                first.y as f64 * s + s * 0.28,
+                (s * 0.25).round(),
+                word.id + 1
+            );
+        }
+    }
+    let _ = writeln!(out, "</g>");
+    let _ = writeln!(out, "</g>");
+    let _ = writeln!(out, "</svg>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+
+    #[test]
+    fn draws_every_letter_and_number() {
+        let mut board = Board::try_from(GRID).unwrap();
+        assert!(board.solve());
+        let options = SvgOptions {
+            numbers: true,
+            ..SvgOptions::default()
+        };
+        let svg = render(&board, &options);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<text").count(), 84 + board.n_words());
+        assert!(svg.contains(&region_color(0)));
+    }
+
+    #[test]
+    fn unsolved_board_has_no_fill() {
+        let board = Board::try_from(GRID).unwrap();
+        let svg = render(&board, &SvgOptions::default());
+        assert!(!svg.contains("hsl("));
+        // Every cell is its own region, so every inner border is drawn
+        assert_eq!(svg.matches("<line").count(), 6 + 11 + 6 * 12 + 7 * 11);
+    }
+}
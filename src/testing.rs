@@ -0,0 +1,17 @@
+//! Fixtures shared by the unit tests
+
+use crate::Puzzle;
+
+/// A board with many solutions, one of which reads each row as a word
+pub const GRID: &str = "flatcar\nsnippet\nrustles\ncondign\nlousier\nspinner\n\
+                        ponchos\nsummary\nsixties\nbogging\nspoofed\nalimony\n";
+
+/// A puzzle whose regions are the rows of `GRID`
+pub fn row_puzzle() -> Puzzle {
+    let regions: Vec<Vec<[usize; 2]>> = (0..12).map(|y| (0..7).map(|x| [x, y]).collect()).collect();
+    serde_json::from_value(serde_json::json!({
+        "width": 7, "height": 12, "minSize": 4, "maxSize": 8,
+        "regions": regions, "words": GRID.lines().collect::<Vec<_>>(),
+    }))
+    .unwrap()
+}
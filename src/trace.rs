@@ -0,0 +1,424 @@
+use crate::frame::{Frame, Reason};
+use crate::play::RawMode;
+use crate::render::{self, Style};
+use crate::report::PuzzleInfo;
+use crate::{Board, ParseError, Point, PointSet, PuzzleError};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Stylize;
+use crossterm::{cursor, execute, terminal};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// The first line of a trace file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Header {
+    puzzle: PuzzleInfo,
+    grid: Vec<String>,
+    /// The fewest and most letters in a word, missing from older traces
+    #[serde(default = "default_word_sizes")]
+    word_sizes: (usize, usize),
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    given: Vec<PointSet>,
+}
+
+/// The word sizes of a board read from a letter grid
+fn default_word_sizes() -> (usize, usize) {
+    (4, 8)
+}
+
+/// A recorded search, written as one json object per line: a header holding
+/// the board, then one line per frame
+#[derive(Clone, Debug)]
+pub struct Trace {
+    /// The letters of the board, one string per row
+    pub grid: Vec<String>,
+    /// The fewest and most letters a word may have
+    pub word_sizes: (usize, usize),
+    /// Words already placed when the search started
+    pub given: Vec<PointSet>,
+    pub frames: Vec<Frame>,
+}
+
+/// Writes a trace a line at a time while the search runs, so frames aren't
+/// held in memory until it ends
+pub struct TraceWriter<W: Write> {
+    out: W,
+    /// The first error writing a frame, returned by `finish`
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Start the trace of a search from this board by writing its header
+    pub fn new(mut out: W, puzzle: PuzzleInfo, board: &Board) -> io::Result<TraceWriter<W>> {
+        let header = Header {
+            puzzle,
+            grid: board.rows(),
+            word_sizes: board.word_sizes(),
+            given: board.words().into_iter().map(|w| w.points).collect(),
+        };
+        write_line(&mut out, &header)?;
+        Ok(TraceWriter { out, error: None })
+    }
+
+    /// Write one frame, giving up on the rest once a write fails
+    pub fn frame(&mut self, frame: &Frame) {
+        if self.error.is_none() {
+            self.error = write_line(&mut self.out, frame).err();
+        }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error {
+            Some(e) => Err(e),
+            None => self.out.flush(),
+        }
+    }
+}
+
+fn write_line<W: Write, T: Serialize>(out: &mut W, value: &T) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, value)?;
+    writeln!(out)
+}
+
+/// Problems reading a trace file
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    /// A line couldn't be understood, numbered from 1
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+    /// The file had no header line
+    Empty,
+    /// The header's letters don't make a board
+    Grid(ParseError),
+    /// The header's word sizes are out of range
+    WordSizes(PuzzleError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "{}", e),
+            ReadError::Json { line, error } => write!(f, "line {}: {}", line, error),
+            ReadError::Empty => write!(f, "empty trace"),
+            ReadError::Grid(e) => write!(f, "{}", e),
+            ReadError::WordSizes(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> ReadError {
+        ReadError::Io(e)
+    }
+}
+
+impl Trace {
+    pub fn read<R: BufRead>(input: R) -> Result<Trace, ReadError> {
+        let mut header: Option<Header> = None;
+        let mut frames = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let json = |error| ReadError::Json { line: i + 1, error };
+            if header.is_none() {
+                header = Some(serde_json::from_str(&line).map_err(json)?);
+            } else {
+                frames.push(serde_json::from_str(&line).map_err(json)?);
+            }
+        }
+        let header = header.ok_or(ReadError::Empty)?;
+        Ok(Trace {
+            grid: header.grid,
+            word_sizes: header.word_sizes,
+            given: header.given,
+            frames,
+        })
+    }
+
+    /// Build the board the trace starts from, with any given words placed
+    pub fn board(&self) -> Result<Board, ReadError> {
+        let mut board = Board::try_from(self.grid.join("\n").as_str()).map_err(ReadError::Grid)?;
+        let (min, max) = self.word_sizes;
+        board
+            .set_word_sizes(min, max)
+            .map_err(ReadError::WordSizes)?;
+        for points in &self.given {
+            board.insert_word(points);
+        }
+        Ok(board)
+    }
+}
+
+/// Apply a frame to the board, moving the replay forward
+fn apply(board: &mut Board, frame: &Frame) {
+    match frame {
+        Frame::Insert { points, .. } => board.insert_word(points),
+        Frame::Remove { points, .. } => board.remove_word(points),
+        Frame::Candidates { .. } | Frame::Solved => {}
+    }
+}
+
+/// Undo a frame, moving the replay back
+fn unapply(board: &mut Board, frame: &Frame) {
+    match frame {
+        Frame::Insert { points, .. } => board.remove_word(points),
+        Frame::Remove { points, .. } => board.insert_word(points),
+        Frame::Candidates { .. } | Frame::Solved => {}
+    }
+}
+
+/// Describe a frame in a line or two, given the board before it
+pub fn describe(board: &Board, frame: &Frame) -> String {
+    match frame {
+        Frame::Candidates {
+            depth,
+            root,
+            words,
+            rejected,
+        } => {
+            let mut names: Vec<String> = words.iter().take(8).map(|w| board.spell(w)).collect();
+            if words.len() > names.len() {
+                names.push(format!("and {} more", words.len() - names.len()));
+            }
+            let count = |reason| rejected.iter().filter(|r| r.reason == reason).count();
+            let mut text = format!(
+                "Word {}: {} candidates at {}: {}\nRejected {} partial words: {} no such word, {} can't be connected, {} not contiguous",
+                depth + 1,
+                words.len(),
+                root,
+                if names.is_empty() { "none".to_string() } else { names.join(", ") },
+                rejected.len(),
+                count(Reason::NoSuchWord),
+                count(Reason::NotConnectable),
+                count(Reason::NotContiguous),
+            );
+            let constrained = count(Reason::Constrained);
+            if constrained > 0 {
+                text.push_str(&format!(", {} ruled out by constraints", constrained));
+            }
+            text
+        }
+        Frame::Insert { depth, word, .. } => format!("Word {}: insert {}", depth + 1, word),
+        Frame::Remove { depth, points } => format!(
+            "Word {}: remove {}, nothing follows from it",
+            depth + 1,
+            board.spell(points)
+        ),
+        Frame::Solved => "Solved".to_string(),
+    }
+}
+
+/// Cells touched by a frame, to point out in the replay
+fn touched(frame: &Frame) -> &[Point] {
+    match frame {
+        Frame::Insert { points, .. } | Frame::Remove { points, .. } => points.points(),
+        Frame::Candidates { root, .. } => std::slice::from_ref(root),
+        Frame::Solved => &[],
+    }
+}
+
+/// Print every frame, with the board after each word is placed or removed
+fn print_all(trace: &Trace, mut board: Board, style: Style) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    for (i, frame) in trace.frames.iter().enumerate() {
+        writeln!(out, "[{}] {}", i + 1, describe(&board, frame))?;
+        apply(&mut board, frame);
+        if !matches!(frame, Frame::Candidates { .. }) {
+            write!(out, "{}", render::render(&board, style))?;
+        }
+    }
+    Ok(())
+}
+
+fn draw(trace: &Trace, board: &Board, pos: usize, style: Style) -> io::Result<()> {
+    // Show the board after the current frame, marking the cells it touched
+    let frame = pos.checked_sub(1).map(|i| &trace.frames[i]);
+    let marked = frame.map(touched).unwrap_or(&[]);
+    let removed = matches!(frame, Some(Frame::Remove { .. }));
+    let text = render::render_marked(board, style, &|point, text| {
+        if !marked.contains(&point) {
+            text
+        } else if removed {
+            text.red().to_string()
+        } else {
+            text.reverse().to_string()
+        }
+    });
+
+    let mut out = io::stdout().lock();
+    execute!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    for line in text.lines() {
+        write!(out, "{}\r\n", line)?;
+    }
+    write!(out, "\r\nFrame {} of {}\r\n", pos, trace.frames.len())?;
+    if let Some(frame) = frame {
+        // Describe against the board as it was before this frame
+        let mut before = board.clone();
+        unapply(&mut before, frame);
+        for line in describe(&before, frame).lines() {
+            write!(out, "{}\r\n", line)?;
+        }
+    }
+    write!(
+        out,
+        "\r\nright/l next, left/h back, pgdn/pgup 100 frames, home/end, q quit\r\n"
+    )?;
+    out.flush()
+}
+
+/// Step through a trace in the terminal, showing the board at each frame
+///
+/// When stdout isn't a terminal every frame is printed in turn instead.
+pub fn replay(trace: &Trace, board: Board, style: Style) -> io::Result<()> {
+    if !io::stdout().is_terminal() {
+        return print_all(trace, board, style);
+    }
+    let _raw = RawMode::enter()?;
+    let mut board = board;
+    let mut pos = 0;
+    loop {
+        draw(trace, &board, pos, style)?;
+        let key = match event::read()? {
+            Event::Key(
+                key @ KeyEvent {
+                    kind: KeyEventKind::Press,
+                    ..
+                },
+            ) => key,
+            _ => continue,
+        };
+        let target = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => pos + 1,
+            KeyCode::Left | KeyCode::Char('h') => pos.saturating_sub(1),
+            KeyCode::PageDown => pos + 100,
+            KeyCode::PageUp => pos.saturating_sub(100),
+            KeyCode::Home => 0,
+            KeyCode::End => trace.frames.len(),
+            _ => pos,
+        };
+        let target = target.min(trace.frames.len());
+        while pos < target {
+            apply(&mut board, &trace.frames[pos]);
+            pos += 1;
+        }
+        while pos > target {
+            pos -= 1;
+            unapply(&mut board, &trace.frames[pos]);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{row_puzzle, GRID};
+    use crate::Puzzle;
+
+    /// Solve the board, writing its trace as the search goes
+    fn record(board: &mut Board) -> (Vec<u8>, Vec<Frame>) {
+        let mut buffer = Vec::new();
+        let mut frames = Vec::new();
+        let mut writer = TraceWriter::new(&mut buffer, PuzzleInfo::default(), board).unwrap();
+        let solved = board.solve_traced(None, &mut |f| {
+            writer.frame(&f);
+            frames.push(f);
+        });
+        assert_eq!(solved, Ok(true));
+        writer.finish().unwrap();
+        (buffer, frames)
+    }
+
+    #[test]
+    fn trace_matches_the_search() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let (buffer, _) = record(&mut board);
+        let trace = Trace::read(buffer.as_slice()).unwrap();
+        assert_eq!(trace.frames.last(), Some(&Frame::Solved));
+
+        let inserts = trace
+            .frames
+            .iter()
+            .filter(|f| matches!(f, Frame::Insert { .. }))
+            .count();
+        let removes = trace
+            .frames
+            .iter()
+            .filter(|f| matches!(f, Frame::Remove { .. }))
+            .count();
+        assert_eq!(inserts as u64, board.stats().nodes);
+        assert_eq!(removes as u64, board.stats().backtracks);
+        assert!(trace.frames.iter().any(|f| match f {
+            Frame::Candidates { rejected, .. } => !rejected.is_empty(),
+            _ => false,
+        }));
+
+        // Replaying every frame ends on the solution the search found
+        let mut replayed = trace.board().unwrap();
+        trace.frames.iter().for_each(|f| apply(&mut replayed, f));
+        assert!(replayed.same_solution(&board));
+        trace
+            .frames
+            .iter()
+            .rev()
+            .for_each(|f| unapply(&mut replayed, f));
+        assert_eq!(replayed.n_words(), 0);
+    }
+
+    #[test]
+    fn trace_round_trip() {
+        let mut board = Board::try_from(GRID).unwrap();
+        let start = board.clone();
+        let (buffer, frames) = record(&mut board);
+        assert_eq!(
+            String::from_utf8_lossy(&buffer).lines().count(),
+            frames.len() + 1
+        );
+        let read = Trace::read(buffer.as_slice()).unwrap();
+        assert_eq!(read.grid, start.rows());
+        assert_eq!(read.frames, frames);
+
+        assert!(matches!(
+            Trace::read(&b"{\"puzzle\":{\"id\":null},\"grid\":[]}\nnope\n"[..]),
+            Err(ReadError::Json { line: 2, .. })
+        ));
+        assert!(matches!(Trace::read(&b""[..]), Err(ReadError::Empty)));
+    }
+
+    #[test]
+    fn word_sizes_are_kept() {
+        let mut board = Board::try_from(Puzzle {
+            min_size: 5,
+            max_size: 7,
+            ..row_puzzle()
+        })
+        .unwrap();
+        let (buffer, _) = record(&mut board);
+        let trace = Trace::read(buffer.as_slice()).unwrap();
+        assert_eq!(trace.board().unwrap().word_sizes(), (5, 7));
+
+        // Traces from before the sizes were written hold letter grids
+        let grid = serde_json::to_string(&board.rows()).unwrap();
+        let old = format!("{{\"puzzle\":{{\"id\":null}},\"grid\":{}}}\n", grid);
+        let trace = Trace::read(old.as_bytes()).unwrap();
+        assert_eq!(trace.board().unwrap().word_sizes(), (4, 8));
+
+        let bad = old.replace("\"grid\"", "\"word_sizes\":[0,8],\"grid\"");
+        let trace = Trace::read(bad.as_bytes()).unwrap();
+        assert!(matches!(trace.board(), Err(ReadError::WordSizes(_))));
+    }
+}
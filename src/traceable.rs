@@ -0,0 +1,113 @@
+use crate::{Board, Point, PointSet};
+use serde::{Deserialize, Serialize};
+
+/// A word traced on the board
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Traced {
+    pub word: String,
+    pub points: PointSet,
+}
+
+/// The words of one length starting at a cell
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByLength {
+    pub length: usize,
+    /// In alphabetical order, with a word traced several ways listed once for
+    /// each
+    pub words: Vec<Traced>,
+}
+
+/// The words starting at one cell
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByStart {
+    pub start: Point,
+    pub letter: char,
+    /// Shortest first
+    pub lengths: Vec<ByLength>,
+}
+
+impl ByStart {
+    pub fn count(&self) -> usize {
+        self.lengths.iter().map(|l| l.words.len()).sum()
+    }
+}
+
+/// Find every dictionary word which could be traced on the free cells of a
+/// board, starting anywhere, grouped by starting cell and then by length
+///
+/// This is what `next_words` would offer if every free cell were the root,
+/// so words needn't fit into any tiling of the rest of the board. Cells with
+/// no words are left out.
+pub fn traceable(board: &Board, min_length: usize) -> Vec<ByStart> {
+    let mut groups: Vec<ByStart> = Vec::new();
+    for points in board.all_words() {
+        if points.length() < min_length {
+            continue;
+        }
+        let start = points.points()[0];
+        if groups.last().is_none_or(|g| g.start != start) {
+            groups.push(ByStart {
+                start,
+                letter: board[start] as char,
+                lengths: Vec::new(),
+            });
+        }
+        let group = groups.last_mut().unwrap();
+        let length = points.length();
+        let i = match group.lengths.binary_search_by_key(&length, |l| l.length) {
+            Ok(i) => i,
+            Err(i) => {
+                group.lengths.insert(
+                    i,
+                    ByLength {
+                        length,
+                        words: Vec::new(),
+                    },
+                );
+                i
+            }
+        };
+        group.lengths[i].words.push(Traced {
+            word: board.spell(&points),
+            points,
+        });
+    }
+    for group in &mut groups {
+        for length in &mut group.lengths {
+            length.words.sort_by(|a, b| a.word.cmp(&b.word));
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GRID;
+
+    #[test]
+    fn groups_by_start_and_length() {
+        let board = Board::try_from(GRID).unwrap();
+        let groups = traceable(&board, 0);
+        let corner = &groups[0];
+        assert_eq!(corner.start, Point::new(0, 0));
+        assert_eq!(corner.letter, 'f');
+        // From the root this is exactly what the solver chooses between
+        assert_eq!(corner.count(), board.next_words().len());
+        assert!(corner.lengths.windows(2).all(|w| w[0].length < w[1].length));
+        let four = &corner.lengths[0];
+        assert_eq!(four.length, 4);
+        assert!(four.words.iter().any(|w| w.word == "flat"));
+
+        // Words are found from cells other than the root too
+        let row = groups.iter().find(|g| g.start == Point::new(0, 5)).unwrap();
+        assert!(row
+            .lengths
+            .iter()
+            .flat_map(|l| &l.words)
+            .any(|w| w.word == "spinner"));
+
+        let long = traceable(&board, 7);
+        assert!(long.iter().flat_map(|g| &g.lengths).all(|l| l.length >= 7));
+    }
+}
@@ -1,21 +1,224 @@
-use isahc::prelude::*;
-use serde::Deserialize;
+#[cfg(feature = "cli")]
+use crate::client::{self, Client};
+use crate::{Point, PointSet};
+#[cfg(feature = "cli")]
+use futures_lite::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+#[cfg(feature = "cli")]
+use std::future::Future;
+#[cfg(feature = "cli")]
+use std::pin::Pin;
+#[cfg(feature = "cli")]
+use std::task::{Context, Poll};
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Puzzle {
     pub width: usize,
     pub height: usize,
-    pub minSize: usize,
-    pub maxSize: usize,
+    #[serde(rename = "minSize")]
+    pub min_size: usize,
+    #[serde(rename = "maxSize")]
+    pub max_size: usize,
     pub regions: Vec<Vec<[usize; 2]>>,
     pub words: Vec<String>,
+    /// Any other fields sent by the site, kept so nothing is lost on re-export
+    ///
+    /// This includes the title, date and author, which are read as text by
+    /// `metadata` but otherwise kept exactly as they were sent.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
+impl Puzzle {
+    /// Return the known descriptive fields which are present, as label value pairs
+    pub fn metadata(&self) -> Vec<(&'static str, String)> {
+        [
+            ("Title", self.title()),
+            ("Date", self.date()),
+            ("Author", self.author()),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| value.map(|v| (label, v)))
+        .collect()
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.text("title")
+    }
+
+    pub fn date(&self) -> Option<String> {
+        self.text("date")
+    }
+
+    pub fn author(&self) -> Option<String> {
+        self.text("author")
+    }
+
+    /// Read a field as text, if it's a string or a number
+    fn text(&self, key: &str) -> Option<String> {
+        match self.extra.get(key)? {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
+    /// The regions of the official solution, as point sets in reading order
+    pub fn official_regions(&self) -> Vec<PointSet> {
+        let mut regions: Vec<PointSet> = self
+            .regions
+            .iter()
+            .map(|region| {
+                let points: Vec<Point> = region
+                    .iter()
+                    .map(|p| Point::new(p[0] as u8, p[1] as u8))
+                    .collect();
+                PointSet::from(points.as_slice()).sorted()
+            })
+            .collect();
+        regions.sort_by_key(|region| {
+            let first = region.points()[0];
+            (first.y, first.x)
+        });
+        regions
+    }
+}
+
+/// Downloading puzzles from the site
+#[cfg(feature = "cli")]
 impl Puzzle {
     /// Load a puzzle from it's id number
-    pub fn from_id(id: usize) -> Result<Puzzle, isahc::Error> {
-        let url = format!("https://www.andrewt.net/puzzles/cell-tower/puzzles/{}.json", id);
-        Ok(isahc::get(url)?.json().unwrap())
+    pub fn from_id(id: usize) -> Result<Puzzle, client::Error> {
+        Puzzle::fetch(&Client::from_env()?, id)
+    }
+
+    /// Load a puzzle from it's id number, using the given client
+    pub fn fetch(client: &Client, id: usize) -> Result<Puzzle, client::Error> {
+        client.get_json(&client.puzzle_url(id))
+    }
+
+    /// Load many puzzles concurrently
+    ///
+    /// At most `limit` requests are in flight at once. Results are yielded as
+    /// soon as they arrive, so they may come back out of order.
+    pub fn fetch_many<I>(client: &Client, ids: I, limit: usize) -> FetchMany<'_, I::IntoIter>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        FetchMany {
+            client,
+            ids: ids.into_iter(),
+            limit: limit.max(1),
+            in_flight: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+type PendingFetch<'a> = Pin<Box<dyn Future<Output = (usize, Result<Puzzle, client::Error>)> + 'a>>;
+
+/// Stream of puzzles being downloaded, created by `Puzzle::fetch_many`
+#[cfg(feature = "cli")]
+pub struct FetchMany<'a, I> {
+    client: &'a Client,
+    ids: I,
+    limit: usize,
+    in_flight: Vec<PendingFetch<'a>>,
+}
+
+#[cfg(feature = "cli")]
+impl<'a, I: Iterator<Item = usize>> FetchMany<'a, I> {
+    /// Start new requests until the limit is reached
+    fn fill(&mut self) {
+        while self.in_flight.len() < self.limit {
+            let id = match self.ids.next() {
+                Some(id) => id,
+                None => break,
+            };
+            let client = self.client;
+            self.in_flight.push(Box::pin(async move {
+                (id, client.get_json_async(&client.puzzle_url(id)).await)
+            }));
+        }
     }
 }
 
+#[cfg(feature = "cli")]
+impl<'a, I: Iterator<Item = usize> + Unpin> Stream for FetchMany<'a, I> {
+    type Item = (usize, Result<Puzzle, client::Error>);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.fill();
+        for i in 0..self.in_flight.len() {
+            if let Poll::Ready(res) = self.in_flight[i].as_mut().poll(cx) {
+                drop(self.in_flight.swap_remove(i));
+                self.fill();
+                return Poll::Ready(Some(res));
+            }
+        }
+        if self.in_flight.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "cli")]
+    use crate::client::tests::{mock_server, reply, test_config};
+
+    #[cfg(feature = "cli")]
+    const PUZZLE: &str = r#"{"width": 7, "height": 12, "minSize": 4, "maxSize": 8,
+        "regions": [[[0, 0]]], "words": ["a"]}"#;
+
+    #[test]
+    fn unknown_fields_survive_a_round_trip() {
+        let text = r#"{"width": 7, "height": 12, "minSize": 4, "maxSize": 8,
+            "regions": [], "words": [], "title": "Towers", "difficulty": {"stars": 3}}"#;
+        let puzzle: Puzzle = serde_json::from_str(text).unwrap();
+        assert_eq!(puzzle.min_size, 4);
+        assert_eq!(puzzle.metadata(), vec![("Title", "Towers".to_string())]);
+
+        let value = serde_json::to_value(&puzzle).unwrap();
+        let original: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn odd_metadata_is_kept_as_sent() {
+        let text = r#"{"width": 7, "height": 12, "minSize": 4, "maxSize": 8,
+            "regions": [], "words": [], "title": null, "date": 20240101,
+            "author": {"name": "Andrew"}}"#;
+        let puzzle: Puzzle = serde_json::from_str(text).unwrap();
+        assert_eq!(puzzle.metadata(), vec![("Date", "20240101".to_string())]);
+
+        let value = serde_json::to_value(&puzzle).unwrap();
+        let original: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn fetch_many_yields_every_id() {
+        let (url, _) = mock_server(vec![
+            reply(200, PUZZLE),
+            reply(500, ""),
+            reply(200, PUZZLE),
+            reply(200, PUZZLE),
+        ]);
+        let client = Client::new(test_config(url)).unwrap();
+        let mut ids: Vec<usize> =
+            futures_lite::stream::block_on(Puzzle::fetch_many(&client, 1..=3, 2))
+                .map(|(id, res)| {
+                    assert_eq!(res.unwrap().words, vec!["a"]);
+                    id
+                })
+                .collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+}